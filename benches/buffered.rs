@@ -0,0 +1,60 @@
+use core::hash::{Hash, Hasher};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use deterministic_hash::digest::DigestHasher;
+use deterministic_hash::siphash::SipHasher13;
+use deterministic_hash::xxhash::Xxh64;
+use deterministic_hash::DeterministicHasher;
+use sha2::Sha256;
+
+/// A struct like one that derives `Hash`, which results in a write per field.
+#[derive(Hash)]
+struct Record {
+    id: u64,
+    kind: u8,
+    flags: u16,
+    offset: i32,
+    position: (u32, u32),
+    weight: u128,
+}
+
+fn records() -> Vec<Record> {
+    (0..1024u32)
+        .map(|i| Record {
+            id: i as u64 * 0x9e37_79b9,
+            kind: i as u8,
+            flags: (i * 7) as u16,
+            offset: -(i as i32),
+            position: (i, i * 3),
+            weight: (i as u128) << 70,
+        })
+        .collect()
+}
+
+fn bench<T: Hasher + Clone>(c: &mut Criterion, name: &str, new: impl Fn() -> T) {
+    let records = records();
+    let mut group = c.benchmark_group(name);
+    group.bench_function(BenchmarkId::new("unbuffered", records.len()), |b| {
+        b.iter(|| {
+            let mut hasher = DeterministicHasher::new(new());
+            records.hash(&mut hasher);
+            hasher.finish()
+        })
+    });
+    group.bench_function(BenchmarkId::new("buffered", records.len()), |b| {
+        b.iter(|| {
+            let mut hasher = DeterministicHasher::new_buffered(new());
+            records.hash(&mut hasher);
+            hasher.finish()
+        })
+    });
+    group.finish();
+}
+
+fn benches(c: &mut Criterion) {
+    bench(c, "siphash13", SipHasher13::new);
+    bench(c, "xxh64", Xxh64::new);
+    bench(c, "sha256", DigestHasher::<Sha256>::default);
+}
+
+criterion_group!(buffered, benches);
+criterion_main!(buffered);
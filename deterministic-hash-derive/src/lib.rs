@@ -0,0 +1,353 @@
+//! Derive macro for the `StableHash` trait of `deterministic-hash`.
+//!
+//! Use it through the `derive` feature of `deterministic-hash`:
+//! ```
+//! use deterministic_hash::StableHash;
+//!
+//! #[derive(StableHash)]
+//! #[stable_hash(domain = "example.Message")]
+//! struct Message {
+//!     id: u32,
+//!     #[stable_hash(skip)]
+//!     cached_len: usize,
+//! }
+//! ```
+//!
+//! Structs are written as their fields in declaration order. Enum variants are written as a `u32`
+//! tag followed by their fields. Tags follow the same rules as Rust discriminants: the first
+//! variant has tag `0` and every other variant has the tag of the previous variant plus one, unless
+//! it is pinned explicitly with an attribute or an integer literal discriminant.
+//!
+//! Supported attributes:
+//! * `#[stable_hash(domain = "...")]` on the type writes the given string before the value, to
+//!   separate types with identical fields.
+//! * `#[stable_hash(tag = N)]` on an enum variant pins its tag.
+//! * `#[stable_hash(skip)]` on a field leaves it out of the hash.
+//! * `#[stable_hash(with = "path")]` on a field hashes it by calling
+//!   `path(&field, state)` instead of `StableHash::stable_hash`.
+//!
+//! # Tagged structs
+//!
+//! Adding a field to a struct changes the hash of every existing value. Structs marked with
+//! `#[stable_hash(tagged)]` instead write every field as a `u32` tag followed by its value, in
+//! ascending tag order, and end with tag `0`. Fields that are equal to their `Default` value, such
+//! as `None`, are left out. A field added with a new tag therefore does not change the hash of
+//! values for which that field is left at its default, in the spirit of protobuf field numbers.
+//!
+//! * `#[stable_hash(tag = N)]` assigns the tag of a field. Every field that is not skipped needs a
+//!   unique tag, starting from `1`. Never reuse the tag of a removed field.
+//! * `#[stable_hash(always)]` writes the field even when it equals its default value, for types
+//!   that do not implement `Default` and `PartialEq`. Such a field can not be added later without
+//!   changing existing hashes.
+//!
+//! ```
+//! use deterministic_hash::StableHash;
+//!
+//! #[derive(StableHash)]
+//! #[stable_hash(tagged)]
+//! struct Record {
+//!     #[stable_hash(tag = 1)]
+//!     id: u32,
+//!     // Added in a later version, records without a comment keep their hash.
+//!     #[stable_hash(tag = 2)]
+//!     comment: Option<String>,
+//! }
+//! ```
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Error, Expr, ExprLit, Fields,
+    Lit, LitInt, LitStr, Path, Result,
+};
+
+#[proc_macro_derive(StableHash, attributes(stable_hash))]
+pub fn derive_stable_hash(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct ContainerAttrs {
+    domain: Option<LitStr>,
+    tagged: bool,
+}
+
+#[derive(Default)]
+struct VariantAttrs {
+    tag: Option<u32>,
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    skip: bool,
+    with: Option<Path>,
+    tag: Option<u32>,
+    always: bool,
+}
+
+fn parse_attrs(
+    attrs: &[Attribute],
+    mut f: impl FnMut(syn::meta::ParseNestedMeta) -> Result<()>,
+) -> Result<()> {
+    for attr in attrs {
+        if attr.path().is_ident("stable_hash") {
+            attr.parse_nested_meta(&mut f)?;
+        }
+    }
+    Ok(())
+}
+
+impl ContainerAttrs {
+    fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut result = Self::default();
+        parse_attrs(attrs, |meta| {
+            if meta.path.is_ident("domain") {
+                result.domain = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("tagged") {
+                result.tagged = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported attribute, expected `domain` or `tagged`"))
+            }
+        })?;
+        Ok(result)
+    }
+}
+
+impl VariantAttrs {
+    fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut result = Self::default();
+        parse_attrs(attrs, |meta| {
+            if meta.path.is_ident("tag") {
+                result.tag = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported attribute, expected `tag`"))
+            }
+        })?;
+        Ok(result)
+    }
+}
+
+impl FieldAttrs {
+    fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut result = Self::default();
+        parse_attrs(attrs, |meta| {
+            if meta.path.is_ident("skip") {
+                result.skip = true;
+                Ok(())
+            } else if meta.path.is_ident("with") {
+                result.with = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("tag") {
+                result.tag = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                Ok(())
+            } else if meta.path.is_ident("always") {
+                result.always = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported attribute, expected `skip`, `with`, `tag` or `always`"))
+            }
+        })?;
+        if result.skip && (result.with.is_some() || result.tag.is_some() || result.always) {
+            return Err(Error::new(
+                Span::call_site(),
+                "`skip` cannot be combined with other attributes",
+            ));
+        }
+        Ok(result)
+    }
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream> {
+    let container = ContainerAttrs::parse(&input.attrs)?;
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let (pattern, fields) = destructure(&data.fields, container.tagged)?;
+            quote! {
+                let Self #pattern = self;
+                #fields
+            }
+        }
+        Data::Enum(data) if container.tagged => {
+            return Err(Error::new(
+                data.enum_token.span(),
+                "`tagged` is only supported on structs",
+            ))
+        }
+        Data::Enum(data) => {
+            let mut arms = Vec::new();
+            let mut used = Vec::new();
+            let mut next = 0u32;
+            for variant in &data.variants {
+                let attrs = VariantAttrs::parse(&variant.attrs)?;
+                let tag = match (attrs.tag, &variant.discriminant) {
+                    (Some(tag), _) => tag,
+                    (None, Some((_, discriminant))) => discriminant_tag(discriminant)?,
+                    (None, None) => next,
+                };
+                if used.contains(&tag) {
+                    return Err(Error::new(
+                        variant.ident.span(),
+                        format!("duplicate tag {}", tag),
+                    ));
+                }
+                used.push(tag);
+                next = tag.wrapping_add(1);
+
+                let ident = &variant.ident;
+                let (pattern, fields) = destructure(&variant.fields, false)?;
+                arms.push(quote! {
+                    Self::#ident #pattern => {
+                        ::deterministic_hash::stable::write_tag(state, #tag);
+                        #fields
+                    }
+                });
+            }
+            if arms.is_empty() {
+                // `match self {}` does not compile, as a reference is never empty.
+                quote! {
+                    match *self {}
+                }
+            } else {
+                quote! {
+                    match self {
+                        #(#arms)*
+                    }
+                }
+            }
+        }
+        Data::Union(data) => {
+            return Err(Error::new(
+                data.union_token.span(),
+                "`StableHash` cannot be derived for unions",
+            ))
+        }
+    };
+
+    let domain = container.domain.map(|domain| {
+        quote! {
+            ::deterministic_hash::StableHash::stable_hash(#domain, state);
+        }
+    });
+
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param
+            .bounds
+            .push(parse_quote!(::deterministic_hash::StableHash));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::deterministic_hash::StableHash for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn stable_hash<__H: ::core::hash::Hasher>(&self, state: &mut __H) {
+                #domain
+                #body
+            }
+        }
+    })
+}
+
+fn discriminant_tag(discriminant: &Expr) -> Result<u32> {
+    match discriminant {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(lit), ..
+        }) => lit.base10_parse(),
+        _ => Err(Error::new(
+            discriminant.span(),
+            "pin the tag of this variant with `#[stable_hash(tag = N)]`",
+        )),
+    }
+}
+
+/// Returns a pattern binding all fields, and the statements hashing them.
+fn destructure(fields: &Fields, tagged: bool) -> Result<(TokenStream, TokenStream)> {
+    let mut bindings = Vec::new();
+    let mut statements = Vec::new();
+    let mut tagged_statements = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let attrs = FieldAttrs::parse(&field.attrs)?;
+        if !tagged && (attrs.tag.is_some() || attrs.always) {
+            return Err(Error::new(
+                field.span(),
+                "`tag` and `always` require `#[stable_hash(tagged)]` on the struct",
+            ));
+        }
+        let binding = format_ident!("__field{}", index);
+        let member = match &field.ident {
+            Some(ident) => quote!(#ident),
+            None => {
+                let index = syn::Index::from(index);
+                quote!(#index)
+            }
+        };
+        bindings.push(quote!(#member: #binding));
+
+        if attrs.skip {
+            continue;
+        }
+        let statement = match attrs.with {
+            Some(with) => quote!(#with(#binding, state);),
+            None => quote!(::deterministic_hash::StableHash::stable_hash(#binding, state);),
+        };
+        if !tagged {
+            statements.push(statement);
+            continue;
+        }
+
+        let tag = match attrs.tag {
+            Some(0) => return Err(Error::new(field.span(), "tag 0 marks the end of a struct")),
+            Some(tag) => tag,
+            None => {
+                return Err(Error::new(
+                    field.span(),
+                    "fields of a tagged struct need `#[stable_hash(tag = N)]` or `skip`",
+                ))
+            }
+        };
+        if tagged_statements.iter().any(|(used, _)| *used == tag) {
+            return Err(Error::new(field.span(), format!("duplicate tag {}", tag)));
+        }
+        let statement = quote! {
+            ::deterministic_hash::stable::write_tag(state, #tag);
+            #statement
+        };
+        tagged_statements.push((
+            tag,
+            if attrs.always {
+                statement
+            } else {
+                quote! {
+                    if !::deterministic_hash::stable::is_default(#binding) {
+                        #statement
+                    }
+                }
+            },
+        ));
+    }
+
+    if tagged {
+        tagged_statements.sort_by_key(|(tag, _)| *tag);
+        statements.extend(
+            tagged_statements
+                .into_iter()
+                .map(|(_, statement)| statement),
+        );
+        statements.push(quote!(::deterministic_hash::stable::write_tag(state, 0);));
+    }
+
+    let pattern = match fields {
+        Fields::Unit => quote!(),
+        _ => quote!({ #(#bindings,)* }),
+    };
+    Ok((pattern, quote!(#(#statements)*)))
+}
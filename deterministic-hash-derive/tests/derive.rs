@@ -0,0 +1,173 @@
+use core::hash::Hasher;
+use deterministic_hash::StableHash;
+
+/// Collects every byte written to it.
+#[derive(Default)]
+struct Bytes(Vec<u8>);
+
+impl Hasher for Bytes {
+    fn finish(&self) -> u64 {
+        0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+}
+
+fn bytes<T: StableHash>(value: &T) -> Vec<u8> {
+    let mut hasher = Bytes::default();
+    value.stable_hash(&mut hasher);
+    hasher.0
+}
+
+fn write_as_u8<H: Hasher>(value: &u32, state: &mut H) {
+    state.write(&[*value as u8]);
+}
+
+#[derive(StableHash)]
+struct Named {
+    a: u8,
+    #[stable_hash(skip)]
+    _cache: u64,
+    #[stable_hash(with = "write_as_u8")]
+    b: u32,
+}
+
+#[derive(StableHash)]
+struct Tuple(u16, #[stable_hash(skip)] u32, bool);
+
+#[derive(StableHash)]
+struct Unit;
+
+#[derive(StableHash)]
+#[stable_hash(domain = "test.Domain")]
+struct Domain(u8);
+
+#[derive(StableHash)]
+struct Generic<T>(T);
+
+#[derive(StableHash)]
+enum Enum {
+    First,
+    Second(u8),
+    #[stable_hash(tag = 10)]
+    Pinned {
+        a: u8,
+    },
+    AfterPinned,
+}
+
+#[derive(StableHash)]
+enum Discriminants {
+    First = 20,
+    Second,
+}
+
+#[derive(StableHash)]
+enum Never {}
+
+#[test]
+fn structs_write_fields_in_order() {
+    assert_eq!(
+        bytes(&Named {
+            a: 1,
+            _cache: 2,
+            b: 3
+        }),
+        [1, 3]
+    );
+    assert_eq!(bytes(&Tuple(1, 2, true)), [1, 0, 1]);
+    assert_eq!(bytes(&Unit), []);
+    assert_eq!(bytes(&Generic(Tuple(1, 2, true))), [1, 0, 1]);
+}
+
+#[test]
+fn domain_is_written_first() {
+    let mut expected = bytes(&"test.Domain");
+    expected.push(7);
+    assert_eq!(bytes(&Domain(7)), expected);
+}
+
+#[test]
+fn enums_write_tags() {
+    assert_eq!(bytes(&Enum::First), [0, 0, 0, 0]);
+    assert_eq!(bytes(&Enum::Second(5)), [1, 0, 0, 0, 5]);
+    assert_eq!(bytes(&Enum::Pinned { a: 5 }), [10, 0, 0, 0, 5]);
+    assert_eq!(bytes(&Enum::AfterPinned), [11, 0, 0, 0]);
+    assert_eq!(bytes(&Discriminants::First), [20, 0, 0, 0]);
+    assert_eq!(bytes(&Discriminants::Second), [21, 0, 0, 0]);
+}
+
+mod v1 {
+    use deterministic_hash::StableHash;
+
+    #[derive(StableHash)]
+    #[stable_hash(tagged)]
+    pub struct Record {
+        #[stable_hash(tag = 1)]
+        pub id: u32,
+        #[stable_hash(tag = 3, always)]
+        pub flag: bool,
+    }
+}
+
+mod v2 {
+    use deterministic_hash::StableHash;
+
+    #[derive(StableHash)]
+    #[stable_hash(tagged)]
+    pub struct Record {
+        #[stable_hash(tag = 3, always)]
+        pub flag: bool,
+        #[stable_hash(tag = 2)]
+        pub comment: Option<String>,
+        #[stable_hash(skip)]
+        pub cache: u64,
+        #[stable_hash(tag = 1)]
+        pub id: u32,
+        #[stable_hash(tag = 4)]
+        pub scores: Vec<u8>,
+    }
+}
+
+#[test]
+fn tagged_structs_write_fields_by_tag() {
+    let record = v2::Record {
+        flag: false,
+        comment: Some("a".to_string()),
+        cache: 0,
+        id: 7,
+        scores: vec![9],
+    };
+    assert_eq!(
+        bytes(&record),
+        [
+            1, 0, 0, 0, 7, 0, 0, 0, // id
+            2, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, b'a', // comment
+            3, 0, 0, 0, 0, // flag
+            4, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 9, // scores
+            0, 0, 0, 0,
+        ]
+    );
+}
+
+#[test]
+fn tagged_structs_skip_default_fields() {
+    let old = v1::Record { id: 0, flag: true };
+    let new = v2::Record {
+        flag: true,
+        comment: None,
+        cache: 5,
+        id: 0,
+        scores: Vec::new(),
+    };
+    assert_eq!(bytes(&old), bytes(&new));
+    assert_eq!(bytes(&old), [3, 0, 0, 0, 1, 0, 0, 0, 0]);
+}
+
+#[test]
+fn empty_enums_derive() {
+    fn stable_hash<T: StableHash>() {}
+    stable_hash::<Never>();
+}
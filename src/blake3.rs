@@ -0,0 +1,156 @@
+//! The BLAKE3 hash function, in its regular, keyed and key derivation modes.
+//!
+//! BLAKE3 is an extendable output function: `Blake3Hasher::finalize_xof_into` fills an output of any
+//! length, of which every shorter output is a prefix.
+//!
+//! ```
+//! use core::hash::Hash;
+//! use deterministic_hash::blake3::DeterministicBlake3;
+//! let mut hasher = DeterministicBlake3::default();
+//! (0x1337 as usize).hash(&mut hasher);
+//! let fingerprint: [u8; 16] = hasher.as_inner().finalize_fixed();
+//! assert_eq!(fingerprint[..4], [0x17, 0xb5, 0x93, 0x3c]);
+//! ```
+
+use crate::{
+    BoundaryIndependent, DeterministicBuildHasher, DeterministicHasher, FixedOutputHasher,
+    HashAlgorithm,
+};
+use core::hash::{BuildHasherDefault, Hasher};
+
+/// The BLAKE3 hasher.
+#[derive(Clone, Debug, Default)]
+pub struct Blake3Hasher(::blake3::Hasher);
+
+/// `DeterministicHasher` around the BLAKE3 hasher.
+pub type DeterministicBlake3 = DeterministicHasher<Blake3Hasher>;
+
+/// `DeterministicBuildHasher` that builds `DeterministicBlake3` hashers.
+pub type BuildDeterministicBlake3 = DeterministicBuildHasher<BuildHasherDefault<Blake3Hasher>>;
+
+impl Blake3Hasher {
+    pub fn new() -> Self {
+        Self(::blake3::Hasher::new())
+    }
+
+    /// Creates a hasher for the keyed mode, to compute a MAC.
+    pub fn new_keyed(key: &[u8; 32]) -> Self {
+        Self(::blake3::Hasher::new_keyed(key))
+    }
+
+    /// Creates a hasher for the key derivation mode.
+    ///
+    /// The context should be hardcoded, globally unique and application-specific.
+    pub fn new_derive_key(context: &str) -> Self {
+        Self(::blake3::Hasher::new_derive_key(context))
+    }
+
+    pub fn as_inner(&self) -> &::blake3::Hasher {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> ::blake3::Hasher {
+        self.0
+    }
+
+    /// Returns the default 32-byte output.
+    pub fn finalize(&self) -> [u8; 32] {
+        *self.0.finalize().as_bytes()
+    }
+
+    /// Fills `output` with the extended output of any length.
+    pub fn finalize_xof_into(&self, output: &mut [u8]) {
+        self.0.finalize_xof().fill(output);
+    }
+
+    /// Returns the first `N` bytes of the extended output.
+    pub fn finalize_fixed<const N: usize>(&self) -> [u8; N] {
+        let mut output = [0; N];
+        self.finalize_xof_into(&mut output);
+        output
+    }
+}
+
+/// Returns the first 8 bytes of the output as a little-endian `u64`.
+impl Hasher for Blake3Hasher {
+    fn finish(&self) -> u64 {
+        u64::from_le_bytes(self.finalize_fixed())
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+}
+
+impl FixedOutputHasher for Blake3Hasher {
+    type Output = [u8; 32];
+
+    fn finish_output(&self) -> [u8; 32] {
+        self.finalize()
+    }
+}
+
+impl HashAlgorithm for Blake3Hasher {
+    fn new_hasher() -> Self {
+        Self::new()
+    }
+}
+
+impl BoundaryIndependent for Blake3Hasher {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Blake3Hasher, DeterministicBlake3};
+    use crate::testing::assert_boundary_independent;
+    use core::hash::{Hash, Hasher};
+
+    const KEY: [u8; 32] = [0x42; 32];
+    const CONTEXT: &str = "deterministic-hash 2024-01-01 blake3 tests";
+
+    #[test]
+    fn modes() {
+        let mut regular = Blake3Hasher::new();
+        let mut keyed = Blake3Hasher::new_keyed(&KEY);
+        let mut derive_key = Blake3Hasher::new_derive_key(CONTEXT);
+        for chunk in [&b"determ"[..], b"inistic"].iter() {
+            regular.write(chunk);
+            keyed.write(chunk);
+            derive_key.write(chunk);
+        }
+        assert_eq!(
+            regular.finalize(),
+            *::blake3::hash(b"deterministic").as_bytes()
+        );
+        assert_eq!(
+            keyed.finalize(),
+            *::blake3::keyed_hash(&KEY, b"deterministic").as_bytes()
+        );
+        assert_eq!(
+            derive_key.finalize(),
+            ::blake3::derive_key(CONTEXT, b"deterministic")
+        );
+    }
+
+    #[test]
+    fn extended_output() {
+        let mut hasher = DeterministicBlake3::default();
+        (0x1337usize, -1isize).hash(&mut hasher);
+        let hasher = hasher.into_inner();
+
+        let short: [u8; 16] = hasher.finalize_fixed();
+        let regular: [u8; 32] = hasher.finalize_fixed();
+        let mut long = [0u8; 64];
+        hasher.finalize_xof_into(&mut long);
+
+        assert_eq!(regular, hasher.finalize());
+        assert_eq!(short, regular[..16]);
+        assert_eq!(regular, long[..32]);
+        let first: [u8; 8] = hasher.finalize_fixed();
+        assert_eq!(hasher.finish(), u64::from_le_bytes(first));
+    }
+
+    #[test]
+    fn boundary_independent() {
+        assert_boundary_independent::<Blake3Hasher>();
+    }
+}
@@ -0,0 +1,66 @@
+//! Buffering of streamed bytes into the fixed-size blocks of a block-based hash function, and
+//! little-endian reads of words from those blocks.
+
+/// Splits written bytes into blocks of `N` bytes, keeping an incomplete last block.
+#[cfg(any(feature = "murmur3", feature = "siphash", feature = "xxhash"))]
+#[derive(Clone, Debug)]
+pub(crate) struct BlockBuffer<const N: usize> {
+    buffer: [u8; N],
+    len: u64,
+}
+
+#[cfg(any(feature = "murmur3", feature = "siphash", feature = "xxhash"))]
+impl<const N: usize> BlockBuffer<N> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            buffer: [0; N],
+            len: 0,
+        }
+    }
+
+    /// The total number of bytes written.
+    pub(crate) fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// The bytes that do not form a complete block yet.
+    pub(crate) fn tail(&self) -> &[u8] {
+        &self.buffer[..(self.len % N as u64) as usize]
+    }
+
+    /// Feeds every block completed by `bytes` into `process`.
+    pub(crate) fn write(&mut self, mut bytes: &[u8], mut process: impl FnMut(&[u8])) {
+        let buffered = (self.len % N as u64) as usize;
+        self.len += bytes.len() as u64;
+
+        if buffered > 0 {
+            let fill = core::cmp::min(N - buffered, bytes.len());
+            self.buffer[buffered..buffered + fill].copy_from_slice(&bytes[..fill]);
+            bytes = &bytes[fill..];
+            if buffered + fill < N {
+                return;
+            }
+            process(&self.buffer);
+        }
+
+        let mut blocks = bytes.chunks_exact(N);
+        for block in &mut blocks {
+            process(block);
+        }
+        let remainder = blocks.remainder();
+        self.buffer[..remainder.len()].copy_from_slice(remainder);
+    }
+}
+
+#[cfg(any(feature = "murmur3", feature = "xxhash", feature = "xxh3"))]
+pub(crate) fn read_u32(bytes: &[u8]) -> u32 {
+    let mut word = [0u8; 4];
+    word.copy_from_slice(&bytes[..4]);
+    u32::from_le_bytes(word)
+}
+
+pub(crate) fn read_u64(bytes: &[u8]) -> u64 {
+    let mut word = [0u8; 8];
+    word.copy_from_slice(&bytes[..8]);
+    u64::from_le_bytes(word)
+}
@@ -0,0 +1,234 @@
+//! Buffering of small writes, such that the inner hasher receives block-aligned chunks.
+//!
+//! A `DeterministicHasher` writes every integer with a separate call to `Hasher::write`, so hashing
+//! a large derived struct results in many writes of 1 to 16 bytes. A `BufferedHasher` collects
+//! those writes in a buffer of `N` bytes on the stack, and passes them to the inner hasher in
+//! multiples of `N` bytes.
+//!
+//! The output is identical to the unbuffered hasher, for every inner hasher whose output only
+//! depends on the concatenation of the written bytes. That holds for all built-in algorithms.
+//!
+//! ```
+//! use core::hash::{Hash, Hasher};
+//! use deterministic_hash::DeterministicHasher;
+//! use std::collections::hash_map::DefaultHasher;
+//!
+//! let value = (0x1337 as usize, [1u8, 2, 3], "abc");
+//! let mut buffered = DeterministicHasher::new_buffered(DefaultHasher::new());
+//! value.hash(&mut buffered);
+//! let mut unbuffered = DeterministicHasher::new(DefaultHasher::new());
+//! value.hash(&mut unbuffered);
+//! assert_eq!(buffered.finish(), unbuffered.finish());
+//! ```
+
+use crate::encoding::LittleEndian;
+use crate::{BoundaryIndependent, DeterministicHasher, FixedOutputHasher, HashAlgorithm};
+use core::hash::Hasher;
+
+/// Wrapper around a hasher that passes written bytes on in chunks of `N` bytes.
+///
+/// `N` should be a multiple of the block size of the inner hasher. The default of 64 bytes is a
+/// multiple of the block size of every built-in algorithm. `N` must not be zero, which is checked
+/// at compile time:
+///
+/// ```compile_fail
+/// use deterministic_hash::buffered::BufferedHasher;
+/// use std::collections::hash_map::DefaultHasher;
+///
+/// let hasher = BufferedHasher::<_, 0>::new(DefaultHasher::new());
+/// ```
+///
+/// The inner hasher receives a write for every `N` bytes, and a write of the last 1 to `N` bytes
+/// when finishing, regardless of how the bytes were written. As such, a `BufferedHasher` is
+/// `BoundaryIndependent` for every inner hasher.
+///
+/// Finishing clones the inner hasher to write the buffered bytes, such that the hasher can still
+/// be written to afterwards.
+#[derive(Clone, Debug)]
+pub struct BufferedHasher<T: Hasher, const N: usize = 64> {
+    inner: T,
+    buffer: [u8; N],
+    len: usize,
+}
+
+/// `DeterministicHasher` that buffers its writes to the hasher `T` in a buffer of `N` bytes.
+pub type BufferedDeterministicHasher<T, const N: usize = 64, E = LittleEndian> =
+    DeterministicHasher<BufferedHasher<T, N>, E>;
+
+impl<T: Hasher, const N: usize> BufferedHasher<T, N> {
+    pub const fn new(inner: T) -> Self {
+        const { assert!(N > 0, "the buffer of a `BufferedHasher` cannot be empty") };
+        Self {
+            inner,
+            buffer: [0; N],
+            len: 0,
+        }
+    }
+
+    /// The bytes that have not been passed to the inner hasher yet.
+    pub fn buffered(&self) -> &[u8] {
+        &self.buffer[..self.len]
+    }
+
+    /// Returns the inner hasher, after passing it the buffered bytes.
+    pub fn into_inner(mut self) -> T {
+        if self.len > 0 {
+            self.inner.write(&self.buffer[..self.len]);
+        }
+        self.inner
+    }
+
+    /// Writes bytes that do not fit in the free part of the buffer.
+    ///
+    /// Keeps the last 1 to `N` bytes in the buffer, such that the chunks do not depend on whether
+    /// the buffer was exactly full.
+    #[cold]
+    fn write_overflowing(&mut self, bytes: &[u8]) {
+        let (fill, bytes) = bytes.split_at(N - self.len);
+        self.buffer[self.len..].copy_from_slice(fill);
+        self.inner.write(&self.buffer);
+
+        let (chunks, remainder) = bytes.split_at((bytes.len() - 1) / N * N);
+        for chunk in chunks.chunks_exact(N) {
+            self.inner.write(chunk);
+        }
+        self.buffer[..remainder.len()].copy_from_slice(remainder);
+        self.len = remainder.len();
+    }
+
+    fn flushed(&self) -> T
+    where
+        T: Clone,
+    {
+        let mut inner = self.inner.clone();
+        if self.len > 0 {
+            inner.write(self.buffered());
+        }
+        inner
+    }
+}
+
+impl<T: Hasher + Clone> DeterministicHasher<BufferedHasher<T>> {
+    /// Wraps the hasher, buffering its writes in a buffer of 64 bytes.
+    pub fn new_buffered(inner: T) -> Self {
+        Self::new(BufferedHasher::new(inner))
+    }
+}
+
+impl<T: Hasher + Default, const N: usize> Default for BufferedHasher<T, N> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: Hasher + Clone, const N: usize> Hasher for BufferedHasher<T, N> {
+    fn finish(&self) -> u64 {
+        self.flushed().finish()
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        match self.buffer.get_mut(self.len..self.len + bytes.len()) {
+            Some(free) => {
+                free.copy_from_slice(bytes);
+                self.len += bytes.len();
+            }
+            None => self.write_overflowing(bytes),
+        }
+    }
+}
+
+impl<T: FixedOutputHasher + Clone, const N: usize> FixedOutputHasher for BufferedHasher<T, N> {
+    type Output = T::Output;
+
+    fn finish_output(&self) -> T::Output {
+        self.flushed().finish_output()
+    }
+}
+
+impl<T: HashAlgorithm + Clone, const N: usize> HashAlgorithm for BufferedHasher<T, N> {
+    fn new_hasher() -> Self {
+        Self::new(T::new_hasher())
+    }
+}
+
+impl<T: Hasher + Clone, const N: usize> BoundaryIndependent for BufferedHasher<T, N> {}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::BufferedHasher;
+    use crate::testing::assert_boundary_independent;
+    use crate::{DeterministicHasher, FixedOutputHasher, HashAlgorithm};
+    use core::hash::{Hash, Hasher};
+    use std::vec::Vec;
+
+    /// Records every write, and hashes the concatenation of all written bytes.
+    ///
+    /// Its output consists of the written bytes and the length of every write, such that it
+    /// depends on the write boundaries.
+    #[derive(Clone, Default)]
+    struct Recorder {
+        bytes: Vec<u8>,
+        writes: Vec<usize>,
+    }
+
+    impl Hasher for Recorder {
+        fn finish(&self) -> u64 {
+            let mut hasher = crc::crc64::Digest::new(crc::crc64::ECMA);
+            hasher.write(&self.bytes);
+            hasher.finish()
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            self.bytes.extend_from_slice(bytes);
+            self.writes.push(bytes.len());
+        }
+    }
+
+    impl FixedOutputHasher for Recorder {
+        type Output = (Vec<u8>, Vec<usize>);
+
+        fn finish_output(&self) -> Self::Output {
+            (self.bytes.clone(), self.writes.clone())
+        }
+    }
+
+    impl HashAlgorithm for Recorder {
+        fn new_hasher() -> Self {
+            Self::default()
+        }
+    }
+
+    #[test]
+    fn matches_unbuffered() {
+        let long: Vec<u32> = (0..100).collect();
+        let value = (1u8, 2u16, -3i32, 4u128, "abc", &long, [5u64; 3]);
+
+        let mut unbuffered = DeterministicHasher::new(Recorder::default());
+        value.hash(&mut unbuffered);
+
+        let mut buffered = DeterministicHasher::new(BufferedHasher::<Recorder, 16>::default());
+        value.hash(&mut buffered);
+        assert_eq!(buffered.finish(), unbuffered.finish());
+
+        // The finish did not consume the buffered bytes.
+        value.hash(&mut unbuffered);
+        value.hash(&mut buffered);
+        assert_eq!(buffered.finish(), unbuffered.finish());
+
+        let inner = buffered.into_inner().into_inner();
+        assert_eq!(inner.bytes, unbuffered.as_inner().bytes);
+        let (last, chunks) = inner.writes.split_last().unwrap();
+        assert!(chunks.iter().all(|len| *len == 16));
+        assert!((1..=16).contains(last));
+    }
+
+    #[test]
+    fn boundary_independent() {
+        assert_boundary_independent::<BufferedHasher<Recorder, 1>>();
+        assert_boundary_independent::<BufferedHasher<Recorder, 16>>();
+        assert_boundary_independent::<BufferedHasher<Recorder>>();
+    }
+}
@@ -0,0 +1,318 @@
+//! Hashing that rejects `usize` and `isize` values that a narrower target could not produce.
+//!
+//! `DeterministicHasher` widens every `usize` to a `u64`, so a 64-bit host can hash lengths and
+//! indices that a 32-bit target never produces. Wrap the hasher in `CheckedUsizeHasher` on the host
+//! to find such values there instead of in the field.
+//!
+//! ```
+//! use core::hash::Hash;
+//! use deterministic_hash::checked::{CheckedUsizeHasher, TargetWidth};
+//! use deterministic_hash::DeterministicHasher;
+//! let hasher = DeterministicHasher::new(crc::crc32::Digest::new(crc::crc32::KOOPMAN));
+//! let mut hasher = CheckedUsizeHasher::new(hasher, TargetWidth::Bits32);
+//! (0x1337 as usize).hash(&mut hasher);
+//! assert_eq!(hasher.try_finish(), Ok(2482448842));
+//! ```
+
+use crate::FixedOutputHasher;
+use core::fmt;
+use core::hash::Hasher;
+
+/// The pointer width of the narrowest target that has to reproduce the hashes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TargetWidth {
+    Bits16,
+    Bits32,
+}
+
+impl TargetWidth {
+    pub const fn bits(self) -> u32 {
+        match self {
+            TargetWidth::Bits16 => 16,
+            TargetWidth::Bits32 => 32,
+        }
+    }
+
+    /// Returns whether a `usize` of this width can hold the value.
+    pub const fn fits_usize(self, value: u64) -> bool {
+        value >> self.bits() == 0
+    }
+
+    /// Returns whether an `isize` of this width can hold the value.
+    pub const fn fits_isize(self, value: i64) -> bool {
+        let half = 1i64 << (self.bits() - 1);
+        -half <= value && value < half
+    }
+}
+
+/// A `usize` or `isize` was written that does not fit the target width.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UsizeOutOfRange {
+    Usize { value: u64, width: TargetWidth },
+    Isize { value: i64, width: TargetWidth },
+}
+
+impl fmt::Display for UsizeOutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UsizeOutOfRange::Usize { value, width } => write!(
+                f,
+                "usize {} does not fit a {}-bit target",
+                value,
+                width.bits()
+            ),
+            UsizeOutOfRange::Isize { value, width } => write!(
+                f,
+                "isize {} does not fit a {}-bit target",
+                value,
+                width.bits()
+            ),
+        }
+    }
+}
+
+impl core::error::Error for UsizeOutOfRange {}
+
+/// Wrapper around any hasher that checks every `usize` and `isize` against a target width.
+///
+/// Values that do not fit are still written to the inner hasher. The first of them is recorded, or
+/// causes a panic when the hasher was created with `panicking`.
+#[derive(Clone, Debug)]
+pub struct CheckedUsizeHasher<T: Hasher> {
+    inner: T,
+    width: TargetWidth,
+    panic: bool,
+    error: Option<UsizeOutOfRange>,
+}
+
+impl<T: Hasher> CheckedUsizeHasher<T> {
+    /// Wraps the hasher, recording the first value that does not fit.
+    pub fn new(inner: T, width: TargetWidth) -> Self {
+        Self {
+            inner,
+            width,
+            panic: false,
+            error: None,
+        }
+    }
+
+    /// Wraps the hasher, panicking on the first value that does not fit.
+    pub fn panicking(inner: T, width: TargetWidth) -> Self {
+        Self {
+            panic: true,
+            ..Self::new(inner, width)
+        }
+    }
+
+    /// Returns the first value written that does not fit the target width.
+    pub fn error(&self) -> Option<UsizeOutOfRange> {
+        self.error
+    }
+
+    /// Returns the hash, or the first value written that does not fit the target width.
+    pub fn try_finish(&self) -> Result<u64, UsizeOutOfRange> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.inner.finish()),
+        }
+    }
+
+    pub fn as_inner(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn check_usize(&mut self, i: usize) {
+        let value = i as u64;
+        if !self.width.fits_usize(value) {
+            self.reject(UsizeOutOfRange::Usize {
+                value,
+                width: self.width,
+            });
+        }
+    }
+
+    fn reject(&mut self, error: UsizeOutOfRange) {
+        if self.panic {
+            panic!("{}", error);
+        }
+        self.error.get_or_insert(error);
+    }
+}
+
+impl<T: Hasher> Hasher for CheckedUsizeHasher<T> {
+    fn finish(&self) -> u64 {
+        self.inner.finish()
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.inner.write(bytes);
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.inner.write_u8(i)
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.inner.write_u16(i)
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.inner.write_u32(i)
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.inner.write_u64(i)
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.inner.write_u128(i)
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.check_usize(i);
+        self.inner.write_usize(i)
+    }
+
+    fn write_i8(&mut self, i: i8) {
+        self.inner.write_i8(i)
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.inner.write_i16(i)
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.inner.write_i32(i)
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.inner.write_i64(i)
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.inner.write_i128(i)
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        let value = i as i64;
+        if !self.width.fits_isize(value) {
+            self.reject(UsizeOutOfRange::Isize {
+                value,
+                width: self.width,
+            });
+        }
+        self.inner.write_isize(i)
+    }
+
+    #[cfg(feature = "nightly")]
+    fn write_length_prefix(&mut self, len: usize) {
+        self.check_usize(len);
+        self.inner.write_length_prefix(len)
+    }
+
+    #[cfg(feature = "nightly")]
+    fn write_str(&mut self, s: &str) {
+        self.check_usize(s.len());
+        self.inner.write_str(s)
+    }
+}
+
+impl<T: FixedOutputHasher> FixedOutputHasher for CheckedUsizeHasher<T> {
+    type Output = T::Output;
+
+    fn finish_output(&self) -> T::Output {
+        self.inner.finish_output()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CheckedUsizeHasher, TargetWidth, UsizeOutOfRange};
+    use crate::DeterministicHasher;
+    use core::hash::Hasher;
+    use crc::crc32::{Digest, KOOPMAN};
+
+    fn hasher(width: TargetWidth) -> CheckedUsizeHasher<DeterministicHasher<Digest>> {
+        CheckedUsizeHasher::new(DeterministicHasher::new(Digest::new(KOOPMAN)), width)
+    }
+
+    #[test]
+    fn in_range() {
+        let mut checked = hasher(TargetWidth::Bits16);
+        checked.write_usize(0xffff);
+        checked.write_isize(-0x8000);
+        checked.write_isize(0x7fff);
+        checked.write_u64(u64::MAX);
+
+        let mut unchecked = DeterministicHasher::new(Digest::new(KOOPMAN));
+        unchecked.write_usize(0xffff);
+        unchecked.write_isize(-0x8000);
+        unchecked.write_isize(0x7fff);
+        unchecked.write_u64(u64::MAX);
+
+        assert_eq!(checked.error(), None);
+        assert_eq!(checked.try_finish(), Ok(unchecked.finish()));
+    }
+
+    #[test]
+    fn out_of_range() {
+        let mut checked = hasher(TargetWidth::Bits16);
+        checked.write_isize(-0x8001);
+        checked.write_usize(0x10000);
+        assert_eq!(
+            checked.try_finish(),
+            Err(UsizeOutOfRange::Isize {
+                value: -0x8001,
+                width: TargetWidth::Bits16
+            })
+        );
+
+        #[cfg(target_pointer_width = "64")]
+        {
+            let mut checked = hasher(TargetWidth::Bits32);
+            checked.write_usize(0xffff_ffff);
+            assert_eq!(checked.error(), None);
+            checked.write_usize(0x1_0000_0000);
+            assert_eq!(
+                checked.error(),
+                Some(UsizeOutOfRange::Usize {
+                    value: 0x1_0000_0000,
+                    width: TargetWidth::Bits32
+                })
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "nightly")]
+    fn long_strings() {
+        extern crate std;
+
+        let long = std::string::String::from_utf8(std::vec![b'a'; 0x10000]).unwrap();
+        let mut checked = hasher(TargetWidth::Bits16);
+        checked.write_str(&long[..0xffff]);
+        assert_eq!(checked.error(), None);
+        checked.write_str(&long);
+        assert_eq!(
+            checked.error(),
+            Some(UsizeOutOfRange::Usize {
+                value: 0x10000,
+                width: TargetWidth::Bits16
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "usize 65536 does not fit a 16-bit target")]
+    fn panicking() {
+        let mut checked = CheckedUsizeHasher::panicking(
+            DeterministicHasher::new(Digest::new(KOOPMAN)),
+            TargetWidth::Bits16,
+        );
+        checked.write_usize(0x10000);
+    }
+}
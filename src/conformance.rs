@@ -0,0 +1,342 @@
+//! A conformance suite for any inner hasher, with a report that can be verified on another target.
+//!
+//! `run` hashes a generated suite of edge-case inputs through a `DeterministicHasher` around hashers
+//! from a factory, and emits a report with the output of every case. `verify` runs the suite on
+//! another target and compares the outputs with the report. Both work without `alloc`, so the
+//! report can be produced on a host and verified on a device, or the other way around. With the
+//! `alloc` feature, `report` returns the report as a `Vec`.
+//!
+//! ```
+//! use deterministic_hash::conformance::{run, verify};
+//!
+//! let factory = || crc::crc32::Digest::new(crc::crc32::CASTAGNOLI);
+//! let mut report = Vec::new();
+//! run(factory, |bytes| report.extend_from_slice(bytes));
+//! // Write the report to a file, and verify it on the other target.
+//! verify(factory, &report).unwrap();
+//! ```
+//!
+//! The suite covers every integer write with the edges of its range, byte writes of every length
+//! up to 257, the same bytes split into writes of every size up to 64, and derived `Hash` impls of
+//! enums, `Option`, `Result` and tuples. `usize` and `isize` values fit in 16 bits, and strings and
+//! integer slices are hashed with `StableHash`, so the report is identical on 16, 32 and 64-bit
+//! targets, with and without the `nightly` feature.
+//!
+//! # Format
+//!
+//! A report starts with the magic bytes `DHCR`, the format version `0x02` and the number of cases
+//! as a little-endian `u32`. Every case follows as its id as a little-endian `u32` and the output
+//! of `Hasher::finish` as a little-endian `u64`. The version changes whenever the suite changes.
+
+use crate::{DeterministicHasher, StableHash};
+use core::fmt;
+use core::hash::{Hash, Hasher};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// The bytes at the start of every report: the magic `DHCR` followed by the format version.
+pub const HEADER: [u8; 5] = *b"DHCR\x02";
+
+/// The number of cases of the suite.
+pub const CASES: u32 = INTEGERS_END + BYTES_LEN + PIECES_LEN + VALUES.len() as u32;
+
+/// The number of bytes of a report.
+pub const REPORT_LEN: usize = HEADER.len() + 4 + CASES as usize * CASE_LEN;
+
+const CASE_LEN: usize = 4 + 8;
+
+/// Bit patterns that are truncated to every integer type, to cover the edges of their ranges.
+const PATTERNS: [u128; 14] = [
+    0,
+    1,
+    0x7f,
+    0x80,
+    0xff,
+    0x7fff,
+    0x8000,
+    0x7fff_ffff,
+    0x8000_0000,
+    0x7fff_ffff_ffff_ffff,
+    0x8000_0000_0000_0000,
+    0x0123_4567_89ab_cdef_fedc_ba98_7654_3210,
+    u128::MAX >> 1,
+    u128::MAX,
+];
+
+const INTEGERS_END: u32 = 12 * PATTERNS.len() as u32;
+const BYTES_LEN: u32 = 258;
+const PIECES_LEN: u32 = 64;
+const PIECES_INPUT: usize = 300;
+
+#[derive(Hash)]
+enum Shape {
+    Point,
+    Circle(u32),
+    Rect { width: u16, height: u16 },
+}
+
+/// Values with derived `Hash` impls and `StableHash` impls.
+const VALUES: &[fn(&mut dyn Hasher)] = &[
+    |mut state| Shape::Point.hash(&mut state),
+    |mut state| Shape::Circle(u32::MAX).hash(&mut state),
+    |mut state| {
+        Shape::Rect {
+            width: 0,
+            height: 1,
+        }
+        .hash(&mut state)
+    },
+    |mut state| None::<u8>.hash(&mut state),
+    |mut state| Some(0u8).hash(&mut state),
+    |mut state| Some(Some(-1i64)).hash(&mut state),
+    |mut state| Ok::<u16, i16>(1).hash(&mut state),
+    |mut state| Err::<u16, i16>(-1).hash(&mut state),
+    |mut state| ((), 0u8, ()).hash(&mut state),
+    |mut state| (true, false, 'a', char::MAX).hash(&mut state),
+    |mut state| ((1u8,), ((2u16, 3u32), -4isize)).hash(&mut state),
+    |mut state| [0u8; 0][..].hash(&mut state),
+    |mut state| [0xffu8; 3][..].hash(&mut state),
+    |mut state| "".stable_hash(&mut state),
+    |mut state| "déjà vu".stable_hash(&mut state),
+    |mut state| ("a", "", "bc").stable_hash(&mut state),
+    |mut state| [-1i32, 0, i32::MAX][..].stable_hash(&mut state),
+    |mut state| [u64::MAX, 0][..].stable_hash(&mut state),
+];
+
+/// Fills the buffer with bytes of the xorshift64 generator.
+fn fill(bytes: &mut [u8], seed: u64) {
+    let mut state = seed | 1;
+    for byte in bytes.iter_mut() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        *byte = state as u8;
+    }
+}
+
+/// Hashes the input of the case `id`.
+fn hash_case(id: u32, state: &mut dyn Hasher) {
+    if id < INTEGERS_END {
+        let pattern = PATTERNS[(id % PATTERNS.len() as u32) as usize];
+        match id / PATTERNS.len() as u32 {
+            0 => state.write_u8(pattern as u8),
+            1 => state.write_u16(pattern as u16),
+            2 => state.write_u32(pattern as u32),
+            3 => state.write_u64(pattern as u64),
+            4 => state.write_u128(pattern),
+            5 => state.write_usize(pattern as u16 as usize),
+            6 => state.write_i8(pattern as i8),
+            7 => state.write_i16(pattern as i16),
+            8 => state.write_i32(pattern as i32),
+            9 => state.write_i64(pattern as i64),
+            10 => state.write_i128(pattern as i128),
+            _ => state.write_isize(pattern as i16 as isize),
+        }
+        return;
+    }
+
+    let id = id - INTEGERS_END;
+    if id < BYTES_LEN {
+        let mut bytes = [0u8; BYTES_LEN as usize - 1];
+        let bytes = &mut bytes[..id as usize];
+        fill(bytes, id as u64);
+        state.write(bytes);
+        return;
+    }
+
+    let id = id - BYTES_LEN;
+    if id < PIECES_LEN {
+        let mut bytes = [0u8; PIECES_INPUT];
+        fill(&mut bytes, PIECES_INPUT as u64);
+        for piece in bytes.chunks(id as usize + 1) {
+            state.write(piece);
+        }
+        return;
+    }
+
+    VALUES[(id - PIECES_LEN) as usize](state);
+}
+
+/// Runs the suite with hashers from `factory`, and passes the report to `emit` in parts.
+pub fn run<H: Hasher>(mut factory: impl FnMut() -> H, mut emit: impl FnMut(&[u8])) {
+    emit(&HEADER);
+    emit(&CASES.to_le_bytes());
+    for id in 0..CASES {
+        let mut hasher = DeterministicHasher::new(factory());
+        hash_case(id, &mut hasher);
+        emit(&id.to_le_bytes());
+        emit(&hasher.finish().to_le_bytes());
+    }
+}
+
+/// Runs the suite with hashers from `factory`, and returns the report.
+#[cfg(feature = "alloc")]
+pub fn report<H: Hasher>(factory: impl FnMut() -> H) -> Vec<u8> {
+    let mut report = Vec::with_capacity(REPORT_LEN);
+    run(factory, |bytes| report.extend_from_slice(bytes));
+    report
+}
+
+/// A report could not be verified.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The report does not start with the magic bytes `DHCR`.
+    Magic,
+    /// The report has a format version, and so a suite, that this version of the crate does not
+    /// have.
+    Version(u8),
+    /// The report does not have `REPORT_LEN` bytes, or another number of cases than `CASES`.
+    Length,
+    /// The report has another case than expected at this position.
+    UnexpectedCase { expected: u32, found: u32 },
+    /// The output of a case on this target differs from the report.
+    Mismatch { id: u32, expected: u64, found: u64 },
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::Magic => write!(f, "not a conformance report"),
+            VerifyError::Version(version) => {
+                write!(f, "unsupported conformance report version {}", version)
+            }
+            VerifyError::Length => write!(f, "conformance report has the wrong length"),
+            VerifyError::UnexpectedCase { expected, found } => {
+                write!(
+                    f,
+                    "expected case {} in the report, found {}",
+                    expected, found
+                )
+            }
+            VerifyError::Mismatch {
+                id,
+                expected,
+                found,
+            } => write!(
+                f,
+                "case {} hashes to {:#018x}, the report has {:#018x}",
+                id, found, expected
+            ),
+        }
+    }
+}
+
+impl core::error::Error for VerifyError {}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    let mut le = [0; 4];
+    le.copy_from_slice(&bytes[..4]);
+    u32::from_le_bytes(le)
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    let mut le = [0; 8];
+    le.copy_from_slice(&bytes[..8]);
+    u64::from_le_bytes(le)
+}
+
+/// Runs the suite with hashers from `factory`, and compares the outputs with the report.
+///
+/// Returns the first case that differs.
+pub fn verify<H: Hasher>(mut factory: impl FnMut() -> H, report: &[u8]) -> Result<(), VerifyError> {
+    if report.len() < HEADER.len() || report[..4] != HEADER[..4] {
+        return Err(VerifyError::Magic);
+    }
+    if report[4] != HEADER[4] {
+        return Err(VerifyError::Version(report[4]));
+    }
+    if report.len() != REPORT_LEN || read_u32(&report[HEADER.len()..]) != CASES {
+        return Err(VerifyError::Length);
+    }
+
+    let cases = report[HEADER.len() + 4..].chunks_exact(CASE_LEN);
+    for (id, case) in (0..CASES).zip(cases) {
+        let found = read_u32(case);
+        if found != id {
+            return Err(VerifyError::UnexpectedCase {
+                expected: id,
+                found,
+            });
+        }
+        let mut hasher = DeterministicHasher::new(factory());
+        hash_case(id, &mut hasher);
+        let (expected, found) = (read_u64(&case[4..]), hasher.finish());
+        if found != expected {
+            return Err(VerifyError::Mismatch {
+                id,
+                expected,
+                found,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{run, verify, VerifyError, CASES, HEADER, REPORT_LEN};
+    use crc::crc32::{self, Hasher32};
+    use std::vec::Vec;
+
+    fn castagnoli() -> crc32::Digest {
+        crc32::Digest::new(crc32::CASTAGNOLI)
+    }
+
+    fn report() -> Vec<u8> {
+        let mut report = Vec::new();
+        run(castagnoli, |bytes| report.extend_from_slice(bytes));
+        report
+    }
+
+    #[test]
+    fn report_is_pinned() {
+        let report = report();
+        assert_eq!(report.len(), REPORT_LEN);
+        assert_eq!(report[..HEADER.len()], HEADER);
+        assert_eq!(report[HEADER.len()..HEADER.len() + 4], CASES.to_le_bytes());
+        // Changes whenever the suite or the format changes, which requires a new version.
+        let mut digest = crc32::Digest::new(crc32::IEEE);
+        digest.write(&report);
+        assert_eq!((CASES, digest.sum32()), (508, 2832274736));
+    }
+
+    #[test]
+    fn verifies() {
+        let report = report();
+        verify(castagnoli, &report).unwrap();
+        let koopman = || crc32::Digest::new(crc32::KOOPMAN);
+        assert!(matches!(
+            verify(koopman, &report),
+            Err(VerifyError::Mismatch { id: 0, .. })
+        ));
+
+        let mut changed = report.clone();
+        let last = changed.len() - 1;
+        changed[last] ^= 1;
+        assert!(matches!(
+            verify(castagnoli, &changed),
+            Err(VerifyError::Mismatch { id, .. }) if id == CASES - 1
+        ));
+        changed[HEADER.len() + 4] = 1;
+        assert_eq!(
+            verify(castagnoli, &changed),
+            Err(VerifyError::UnexpectedCase {
+                expected: 0,
+                found: 1
+            })
+        );
+
+        assert_eq!(
+            verify(castagnoli, &report[..last]),
+            Err(VerifyError::Length)
+        );
+        assert_eq!(verify(castagnoli, b"DHT\x01"), Err(VerifyError::Magic));
+        assert_eq!(
+            verify(castagnoli, b"DHCR\x01"),
+            Err(VerifyError::Version(1))
+        );
+    }
+}
@@ -0,0 +1,742 @@
+//! Cyclic redundancy checks, configurable for any algorithm of the CRC RevEng catalogue.
+//!
+//! A `Crc` combines an `Algorithm` with one of three backends:
+//! * `Bitwise` uses no tables, for when flash is tight.
+//! * `Table` uses a table of 256 entries.
+//! * `SliceBy8` uses eight tables of 256 entries, and processes 8 bytes at a time.
+//!
+//! `Crc::new` is a `const fn`, such that the tables are generated at compile time when the `Crc` is
+//! stored in a `const` or `static`. A `Digest` of a `Crc` implements `Hasher`, and its `finalize`
+//! returns the CRC in its native width.
+//!
+//! ```
+//! use core::hash::{Hash, Hasher};
+//! use deterministic_hash::crc::{Crc, CRC_32_ISO_HDLC};
+//! use deterministic_hash::{DeterministicHasher, FixedOutputHasher};
+//!
+//! static CRC: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+//! assert_eq!(CRC.checksum(b"123456789"), 0xcbf43926);
+//!
+//! let mut hasher = DeterministicHasher::new(CRC.digest());
+//! (0x1337 as usize).hash(&mut hasher);
+//! assert_eq!(hasher.finish_output(), 0xbc1c035e);
+//! ```
+//!
+//! The most common algorithms also have their own hasher types, such as `Crc32`, which can be
+//! passed as a `HashAlgorithm` to `hash_one` and `hash_bytes`.
+//!
+//! ```
+//! use deterministic_hash::crc::{Crc32, Crc32Koopman};
+//! use deterministic_hash::{hash_bytes, hash_one};
+//!
+//! assert_eq!(hash_bytes::<Crc32, _>(b"123456789"), 0xcbf43926);
+//! assert_eq!(hash_one::<Crc32Koopman, _>(&(0x1337 as usize)), 2482448842);
+//! ```
+
+use crate::{
+    BoundaryIndependent, DeterministicBuildHasher, DeterministicHasher, FixedOutputHasher,
+    HashAlgorithm,
+};
+use core::hash::{BuildHasherDefault, Hasher};
+
+/// The parameters of a CRC algorithm, as listed in the CRC RevEng catalogue.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Algorithm<W> {
+    /// The number of bits of the CRC, at most the number of bits of `W`.
+    pub width: u8,
+    /// The generator polynomial, without its leading term and not reflected.
+    pub poly: W,
+    /// The initial value of the register, not reflected.
+    pub init: W,
+    /// Whether the bits of every input byte are processed least significant first.
+    pub refin: bool,
+    /// Whether the register is reflected before `xorout` is applied.
+    pub refout: bool,
+    /// The value xored into the result.
+    pub xorout: W,
+    /// The CRC of the ASCII string `"123456789"`.
+    pub check: W,
+    /// The register before `xorout`, after processing a message followed by its CRC.
+    pub residue: W,
+}
+
+pub const CRC_5_USB: Algorithm<u8> = Algorithm {
+    width: 5,
+    poly: 0x05,
+    init: 0x1f,
+    refin: true,
+    refout: true,
+    xorout: 0x1f,
+    check: 0x19,
+    residue: 0x06,
+};
+
+pub const CRC_8_SMBUS: Algorithm<u8> = Algorithm {
+    width: 8,
+    poly: 0x07,
+    init: 0x00,
+    refin: false,
+    refout: false,
+    xorout: 0x00,
+    check: 0xf4,
+    residue: 0x00,
+};
+
+pub const CRC_16_ARC: Algorithm<u16> = Algorithm {
+    width: 16,
+    poly: 0x8005,
+    init: 0x0000,
+    refin: true,
+    refout: true,
+    xorout: 0x0000,
+    check: 0xbb3d,
+    residue: 0x0000,
+};
+
+/// Also known as CRC-16/CCITT-FALSE.
+pub const CRC_16_IBM_3740: Algorithm<u16> = Algorithm {
+    width: 16,
+    poly: 0x1021,
+    init: 0xffff,
+    refin: false,
+    refout: false,
+    xorout: 0x0000,
+    check: 0x29b1,
+    residue: 0x0000,
+};
+
+/// Also known as CRC-16/X-25.
+pub const CRC_16_IBM_SDLC: Algorithm<u16> = Algorithm {
+    width: 16,
+    poly: 0x1021,
+    init: 0xffff,
+    refin: true,
+    refout: true,
+    xorout: 0xffff,
+    check: 0x906e,
+    residue: 0xf0b8,
+};
+
+/// Also known as CRC-16/CCITT.
+pub const CRC_16_KERMIT: Algorithm<u16> = Algorithm {
+    width: 16,
+    poly: 0x1021,
+    init: 0x0000,
+    refin: true,
+    refout: true,
+    xorout: 0x0000,
+    check: 0x2189,
+    residue: 0x0000,
+};
+
+pub const CRC_16_MODBUS: Algorithm<u16> = Algorithm {
+    width: 16,
+    poly: 0x8005,
+    init: 0xffff,
+    refin: true,
+    refout: true,
+    xorout: 0x0000,
+    check: 0x4b37,
+    residue: 0x0000,
+};
+
+pub const CRC_16_XMODEM: Algorithm<u16> = Algorithm {
+    width: 16,
+    poly: 0x1021,
+    init: 0x0000,
+    refin: false,
+    refout: false,
+    xorout: 0x0000,
+    check: 0x31c3,
+    residue: 0x0000,
+};
+
+pub const CRC_24_OPENPGP: Algorithm<u32> = Algorithm {
+    width: 24,
+    poly: 0x864cfb,
+    init: 0xb704ce,
+    refin: false,
+    refout: false,
+    xorout: 0x000000,
+    check: 0x21cf02,
+    residue: 0x000000,
+};
+
+pub const CRC_32_BZIP2: Algorithm<u32> = Algorithm {
+    width: 32,
+    poly: 0x04c11db7,
+    init: 0xffffffff,
+    refin: false,
+    refout: false,
+    xorout: 0xffffffff,
+    check: 0xfc891918,
+    residue: 0xc704dd7b,
+};
+
+/// Also known as CRC-32C, the Castagnoli CRC.
+pub const CRC_32_ISCSI: Algorithm<u32> = Algorithm {
+    width: 32,
+    poly: 0x1edc6f41,
+    init: 0xffffffff,
+    refin: true,
+    refout: true,
+    xorout: 0xffffffff,
+    check: 0xe3069283,
+    residue: 0xb798b438,
+};
+
+/// The CRC of Ethernet, zlib and PNG.
+pub const CRC_32_ISO_HDLC: Algorithm<u32> = Algorithm {
+    width: 32,
+    poly: 0x04c11db7,
+    init: 0xffffffff,
+    refin: true,
+    refout: true,
+    xorout: 0xffffffff,
+    check: 0xcbf43926,
+    residue: 0xdebb20e3,
+};
+
+/// The Koopman polynomial with the parameters of `crc::crc32::KOOPMAN` of the `crc` 1.x crate.
+///
+/// This algorithm is not part of the CRC RevEng catalogue.
+pub const CRC_32_KOOPMAN: Algorithm<u32> = Algorithm {
+    width: 32,
+    poly: 0x741b8cd7,
+    init: 0xffffffff,
+    refin: true,
+    refout: true,
+    xorout: 0xffffffff,
+    check: 0x2d3dd0ae,
+    residue: 0x0843323b,
+};
+
+pub const CRC_32_MPEG_2: Algorithm<u32> = Algorithm {
+    width: 32,
+    poly: 0x04c11db7,
+    init: 0xffffffff,
+    refin: false,
+    refout: false,
+    xorout: 0x00000000,
+    check: 0x0376e6e7,
+    residue: 0x00000000,
+};
+
+pub const CRC_64_ECMA_182: Algorithm<u64> = Algorithm {
+    width: 64,
+    poly: 0x42f0e1eba9ea3693,
+    init: 0x0000000000000000,
+    refin: false,
+    refout: false,
+    xorout: 0x0000000000000000,
+    check: 0x6c40df5f0b497347,
+    residue: 0x0000000000000000,
+};
+
+pub const CRC_64_XZ: Algorithm<u64> = Algorithm {
+    width: 64,
+    poly: 0x42f0e1eba9ea3693,
+    init: 0xffffffffffffffff,
+    refin: true,
+    refout: true,
+    xorout: 0xffffffffffffffff,
+    check: 0x995dc9bbdf1939fa,
+    residue: 0x49958c9abd7d353f,
+};
+
+/// The way a `Crc` processes bytes, and the tables it needs for that.
+pub trait Implementation {
+    type Data<W>;
+}
+
+/// Processes every bit on its own, without tables.
+#[derive(Clone, Copy, Debug)]
+pub struct Bitwise;
+
+/// Processes a byte at a time, using a table of 256 entries.
+#[derive(Clone, Copy, Debug)]
+pub struct Table;
+
+/// Processes 8 bytes at a time, using 8 tables of 256 entries.
+#[derive(Clone, Copy, Debug)]
+pub struct SliceBy8;
+
+impl Implementation for Bitwise {
+    type Data<W> = ();
+}
+
+impl Implementation for Table {
+    type Data<W> = [W; 256];
+}
+
+impl Implementation for SliceBy8 {
+    type Data<W> = [[W; 256]; 8];
+}
+
+/// A CRC algorithm of width `W` with the tables of its backend.
+pub struct Crc<W: 'static, I: Implementation = Table> {
+    algorithm: &'static Algorithm<W>,
+    data: I::Data<W>,
+}
+
+/// A running CRC computation.
+pub struct Digest<'a, W: 'static, I: Implementation = Table> {
+    crc: &'a Crc<W, I>,
+    value: W,
+}
+
+impl<W, I: Implementation> Crc<W, I> {
+    pub const fn algorithm(&self) -> &'static Algorithm<W> {
+        self.algorithm
+    }
+}
+
+impl<W: Copy, I: Implementation> Clone for Digest<'_, W, I> {
+    fn clone(&self) -> Self {
+        Self {
+            crc: self.crc,
+            value: self.value,
+        }
+    }
+}
+
+macro_rules! crc_width {
+    ($W:ty) => {
+        impl Algorithm<$W> {
+            const BITS: u32 = <$W>::BITS;
+
+            const fn reflect(&self, value: $W) -> $W {
+                value.reverse_bits() >> (Self::BITS - self.width as u32)
+            }
+
+            /// The register is reflected when `refin` is set, and aligned to the most
+            /// significant bit otherwise.
+            const fn register(&self, value: $W) -> $W {
+                if self.refin {
+                    self.reflect(value)
+                } else {
+                    value << (Self::BITS - self.width as u32)
+                }
+            }
+
+            const fn initial(&self) -> $W {
+                self.register(self.init)
+            }
+
+            const fn finalize(&self, mut crc: $W) -> $W {
+                if !self.refin {
+                    crc >>= Self::BITS - self.width as u32;
+                }
+                if self.refin != self.refout {
+                    crc = self.reflect(crc);
+                }
+                crc ^ self.xorout
+            }
+
+            /// Shifts the register by a byte, towards the end that is processed first.
+            const fn shift_byte(&self, crc: $W) -> $W {
+                let shifted = if self.refin {
+                    crc.checked_shr(8)
+                } else {
+                    crc.checked_shl(8)
+                };
+                match shifted {
+                    Some(shifted) => shifted,
+                    None => 0,
+                }
+            }
+
+            /// The byte of the register that is processed first.
+            const fn first_byte(&self, crc: $W) -> u8 {
+                if self.refin {
+                    crc as u8
+                } else {
+                    (crc >> (Self::BITS - 8)) as u8
+                }
+            }
+
+            const fn process_byte(&self, poly: $W, crc: $W, byte: u8) -> $W {
+                let mut crc = if self.refin {
+                    crc ^ byte as $W
+                } else {
+                    crc ^ (byte as $W) << (Self::BITS - 8)
+                };
+                let mut i = 0;
+                while i < 8 {
+                    crc = if self.refin {
+                        if crc & 1 != 0 {
+                            (crc >> 1) ^ poly
+                        } else {
+                            crc >> 1
+                        }
+                    } else if crc >> (Self::BITS - 1) != 0 {
+                        (crc << 1) ^ poly
+                    } else {
+                        crc << 1
+                    };
+                    i += 1;
+                }
+                crc
+            }
+
+            const fn bitwise(&self) {}
+
+            const fn table(&self) -> [$W; 256] {
+                let poly = self.register(self.poly);
+                let mut table = [0; 256];
+                let mut i = 0;
+                while i < 256 {
+                    table[i] = self.process_byte(poly, 0, i as u8);
+                    i += 1;
+                }
+                table
+            }
+
+            const fn slice_by_8(&self) -> [[$W; 256]; 8] {
+                let mut tables = [[0; 256]; 8];
+                tables[0] = self.table();
+                let mut k = 1;
+                while k < 8 {
+                    let mut i = 0;
+                    while i < 256 {
+                        let previous = tables[k - 1][i];
+                        tables[k][i] = tables[0][self.first_byte(previous) as usize]
+                            ^ self.shift_byte(previous);
+                        i += 1;
+                    }
+                    k += 1;
+                }
+                tables
+            }
+
+            const fn update_bitwise(&self, _: &(), mut crc: $W, bytes: &[u8]) -> $W {
+                let poly = self.register(self.poly);
+                let mut i = 0;
+                while i < bytes.len() {
+                    crc = self.process_byte(poly, crc, bytes[i]);
+                    i += 1;
+                }
+                crc
+            }
+
+            const fn update_table(&self, table: &[$W; 256], mut crc: $W, bytes: &[u8]) -> $W {
+                let mut i = 0;
+                while i < bytes.len() {
+                    crc = table[(self.first_byte(crc) ^ bytes[i]) as usize] ^ self.shift_byte(crc);
+                    i += 1;
+                }
+                crc
+            }
+
+            const fn update_slice_by_8(
+                &self,
+                tables: &[[$W; 256]; 8],
+                mut crc: $W,
+                bytes: &[u8],
+            ) -> $W {
+                let mut i = 0;
+                while i + 8 <= bytes.len() {
+                    // Every byte of the register is absorbed by the 8 input bytes, so the result
+                    // is the effect of each byte followed by the remaining bytes as zeroes.
+                    let mut next = 0;
+                    let mut register = crc;
+                    let mut j = 0;
+                    while j < 8 {
+                        let byte = bytes[i + j] ^ self.first_byte(register);
+                        next ^= tables[7 - j][byte as usize];
+                        register = self.shift_byte(register);
+                        j += 1;
+                    }
+                    crc = next;
+                    i += 8;
+                }
+                while i < bytes.len() {
+                    crc = tables[0][(self.first_byte(crc) ^ bytes[i]) as usize]
+                        ^ self.shift_byte(crc);
+                    i += 1;
+                }
+                crc
+            }
+        }
+
+        crc_implementation!($W, Bitwise, bitwise, update_bitwise);
+        crc_implementation!($W, Table, table, update_table);
+        crc_implementation!($W, SliceBy8, slice_by_8, update_slice_by_8);
+    };
+}
+
+macro_rules! crc_implementation {
+    ($W:ty, $I:ty, $data:ident, $update:ident) => {
+        impl Crc<$W, $I> {
+            /// Creates the CRC and generates the tables of its backend.
+            pub const fn new(algorithm: &'static Algorithm<$W>) -> Self {
+                Self {
+                    algorithm,
+                    data: algorithm.$data(),
+                }
+            }
+
+            /// Returns the CRC of `bytes`.
+            pub const fn checksum(&self, bytes: &[u8]) -> $W {
+                let crc = self
+                    .algorithm
+                    .$update(&self.data, self.algorithm.initial(), bytes);
+                self.algorithm.finalize(crc)
+            }
+
+            pub const fn digest(&self) -> Digest<'_, $W, $I> {
+                Digest {
+                    crc: self,
+                    value: self.algorithm.initial(),
+                }
+            }
+        }
+
+        impl Digest<'_, $W, $I> {
+            pub fn update(&mut self, bytes: &[u8]) {
+                self.value = self
+                    .crc
+                    .algorithm
+                    .$update(&self.crc.data, self.value, bytes);
+            }
+
+            /// Returns the CRC of the bytes written so far.
+            pub const fn finalize(&self) -> $W {
+                self.crc.algorithm.finalize(self.value)
+            }
+        }
+
+        /// Returns the CRC zero-extended to a `u64`.
+        impl Hasher for Digest<'_, $W, $I> {
+            fn finish(&self) -> u64 {
+                self.finalize() as u64
+            }
+
+            fn write(&mut self, bytes: &[u8]) {
+                self.update(bytes);
+            }
+        }
+
+        impl FixedOutputHasher for Digest<'_, $W, $I> {
+            type Output = $W;
+
+            fn finish_output(&self) -> $W {
+                self.finalize()
+            }
+        }
+
+        impl BoundaryIndependent for Digest<'_, $W, $I> {}
+    };
+}
+
+crc_width!(u8);
+crc_width!(u16);
+crc_width!(u32);
+crc_width!(u64);
+
+macro_rules! crc_hasher {
+    ($(#[$doc:meta])* $name:ident, $deterministic:ident, $build:ident, $W:ty, $algorithm:ident) => {
+        $(#[$doc])*
+        ///
+        /// Uses a table that is generated at compile time.
+        #[derive(Clone)]
+        pub struct $name(Digest<'static, $W>);
+
+        #[doc = concat!("`DeterministicHasher` around the `", stringify!($name), "` hasher.")]
+        pub type $deterministic = DeterministicHasher<$name>;
+
+        #[doc = concat!("`DeterministicBuildHasher` that builds `", stringify!($deterministic), "` hashers.")]
+        pub type $build = DeterministicBuildHasher<BuildHasherDefault<$name>>;
+
+        impl $name {
+            pub const fn new() -> Self {
+                // A `static`, such that every hasher shares a single copy of the table.
+                static CRC: Crc<$W> = Crc::<$W>::new(&$algorithm);
+                Self(Digest {
+                    crc: &CRC,
+                    value: $algorithm.initial(),
+                })
+            }
+
+            /// Returns the CRC of the bytes written so far.
+            pub const fn finalize(&self) -> $W {
+                self.0.finalize()
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        /// Returns the CRC zero-extended to a `u64`.
+        impl Hasher for $name {
+            fn finish(&self) -> u64 {
+                self.0.finish()
+            }
+
+            fn write(&mut self, bytes: &[u8]) {
+                self.0.update(bytes);
+            }
+        }
+
+        impl FixedOutputHasher for $name {
+            type Output = $W;
+
+            fn finish_output(&self) -> $W {
+                self.finalize()
+            }
+        }
+
+        impl HashAlgorithm for $name {
+            fn new_hasher() -> Self {
+                Self::new()
+            }
+        }
+
+        impl BoundaryIndependent for $name {}
+    };
+}
+
+crc_hasher!(
+    /// The CRC-16/KERMIT hasher, also known as CRC-16/CCITT.
+    Crc16Ccitt,
+    DeterministicCrc16Ccitt,
+    BuildDeterministicCrc16Ccitt,
+    u16,
+    CRC_16_KERMIT
+);
+crc_hasher!(
+    /// The CRC-32/ISO-HDLC hasher, the CRC of Ethernet, zlib and PNG.
+    Crc32,
+    DeterministicCrc32,
+    BuildDeterministicCrc32,
+    u32,
+    CRC_32_ISO_HDLC
+);
+crc_hasher!(
+    /// The CRC-32/ISCSI hasher, also known as CRC-32C.
+    Crc32c,
+    DeterministicCrc32c,
+    BuildDeterministicCrc32c,
+    u32,
+    CRC_32_ISCSI
+);
+crc_hasher!(
+    /// The CRC-32/KOOPMAN hasher, compatible with `crc::crc32::KOOPMAN` of the `crc` 1.x crate.
+    Crc32Koopman,
+    DeterministicCrc32Koopman,
+    BuildDeterministicCrc32Koopman,
+    u32,
+    CRC_32_KOOPMAN
+);
+crc_hasher!(
+    /// The CRC-64/XZ hasher.
+    Crc64Xz,
+    DeterministicCrc64Xz,
+    BuildDeterministicCrc64Xz,
+    u64,
+    CRC_64_XZ
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::assert_boundary_independent;
+
+    const CHECK: &[u8] = b"123456789";
+
+    macro_rules! assert_algorithm {
+        ($W:ty, $algorithm:expr) => {{
+            let bitwise = Crc::<$W, Bitwise>::new(&$algorithm);
+            let table = Crc::<$W, Table>::new(&$algorithm);
+            let slice_by_8 = Crc::<$W, SliceBy8>::new(&$algorithm);
+            assert_eq!(bitwise.checksum(CHECK), $algorithm.check);
+            assert_eq!(table.checksum(CHECK), $algorithm.check);
+            assert_eq!(slice_by_8.checksum(CHECK), $algorithm.check);
+
+            let input = [0xa5u8; 37];
+            let expected = bitwise.checksum(&input);
+            for chunk_size in 1..=17 {
+                let mut digests = (bitwise.digest(), table.digest(), slice_by_8.digest());
+                for chunk in input.chunks(chunk_size) {
+                    digests.0.write(chunk);
+                    digests.1.write(chunk);
+                    digests.2.write(chunk);
+                }
+                assert_eq!(digests.0.finalize(), expected);
+                assert_eq!(digests.1.finalize(), expected);
+                assert_eq!(digests.2.finalize(), expected);
+                assert_eq!(digests.2.finish(), expected as u64);
+            }
+
+            if $algorithm.width % 8 == 0 {
+                // Append the CRC in the order in which its bits are transmitted.
+                let bytes = if $algorithm.refout {
+                    $algorithm.check.to_le_bytes()
+                } else {
+                    ($algorithm.check << (<$W>::BITS - $algorithm.width as u32)).to_be_bytes()
+                };
+                let mut digest = table.digest();
+                digest.write(CHECK);
+                digest.write(&bytes[..$algorithm.width as usize / 8]);
+                assert_eq!(digest.finalize() ^ $algorithm.xorout, $algorithm.residue);
+            }
+        }};
+    }
+
+    #[test]
+    fn catalogue() {
+        assert_algorithm!(u8, CRC_5_USB);
+        assert_algorithm!(u8, CRC_8_SMBUS);
+        assert_algorithm!(u16, CRC_16_ARC);
+        assert_algorithm!(u16, CRC_16_IBM_3740);
+        assert_algorithm!(u16, CRC_16_IBM_SDLC);
+        assert_algorithm!(u16, CRC_16_KERMIT);
+        assert_algorithm!(u16, CRC_16_MODBUS);
+        assert_algorithm!(u16, CRC_16_XMODEM);
+        assert_algorithm!(u32, CRC_24_OPENPGP);
+        assert_algorithm!(u32, CRC_32_BZIP2);
+        assert_algorithm!(u32, CRC_32_ISCSI);
+        assert_algorithm!(u32, CRC_32_ISO_HDLC);
+        assert_algorithm!(u32, CRC_32_KOOPMAN);
+        assert_algorithm!(u32, CRC_32_MPEG_2);
+        assert_algorithm!(u64, CRC_64_ECMA_182);
+        assert_algorithm!(u64, CRC_64_XZ);
+    }
+
+    #[test]
+    fn hashers() {
+        use crate::hash_bytes;
+
+        assert_eq!(hash_bytes::<Crc16Ccitt, _>(CHECK), CRC_16_KERMIT.check);
+        assert_eq!(hash_bytes::<Crc32, _>(CHECK), CRC_32_ISO_HDLC.check);
+        assert_eq!(hash_bytes::<Crc32c, _>(CHECK), CRC_32_ISCSI.check);
+        assert_eq!(hash_bytes::<Crc32Koopman, _>(CHECK), CRC_32_KOOPMAN.check);
+        assert_eq!(hash_bytes::<Crc64Xz, _>(CHECK), CRC_64_XZ.check);
+    }
+
+    #[test]
+    fn koopman_matches_crc_1() {
+        use crate::DeterministicHasher;
+        use core::hash::Hash;
+        use crc::crc32::{Digest, Hasher32, KOOPMAN};
+
+        static CRC: Crc<u32, SliceBy8> = Crc::<u32, SliceBy8>::new(&CRC_32_KOOPMAN);
+        let mut reference = DeterministicHasher::new(Digest::new(KOOPMAN));
+        let mut hasher = DeterministicHasher::new(CRC.digest());
+        (0x1337usize, "koopman", -1i16).hash(&mut reference);
+        (0x1337usize, "koopman", -1i16).hash(&mut hasher);
+        assert_eq!(hasher.finish_output(), reference.as_inner().sum32());
+    }
+
+    #[test]
+    fn boundary_independent() {
+        assert_boundary_independent::<Crc16Ccitt>();
+        assert_boundary_independent::<Crc32>();
+        assert_boundary_independent::<Crc32c>();
+        assert_boundary_independent::<Crc32Koopman>();
+        assert_boundary_independent::<Crc64Xz>();
+    }
+}
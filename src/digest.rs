@@ -0,0 +1,123 @@
+//! Adapter to use any RustCrypto `digest` implementation, like SHA-256, SHA-3 or BLAKE2, as a hasher.
+//!
+//! `Hasher::finish` truncates the output to a `u64`. Use `DigestHasher::finalize` to obtain the full
+//! output of the digest instead.
+//!
+//! ```
+//! use core::hash::Hash;
+//! use deterministic_hash::digest::DeterministicDigest;
+//! use sha2::Sha256;
+//! let mut hasher = DeterministicDigest::<Sha256>::default();
+//! (0x1337 as usize).hash(&mut hasher);
+//! let output = hasher.into_inner().finalize();
+//! assert_eq!(output[..4], [0x4b, 0x4e, 0xc1, 0x2a]);
+//! ```
+
+use crate::{
+    BoundaryIndependent, DeterministicBuildHasher, DeterministicHasher, FixedOutputHasher,
+    HashAlgorithm,
+};
+use ::digest::{FixedOutput, Output, Update};
+use core::hash::{BuildHasherDefault, Hasher};
+
+/// Hasher that feeds all bytes written into the digest `D`.
+#[derive(Clone, Debug, Default)]
+pub struct DigestHasher<D>(D);
+
+/// `DeterministicHasher` around a digest.
+pub type DeterministicDigest<D> = DeterministicHasher<DigestHasher<D>>;
+
+/// `DeterministicBuildHasher` that builds `DeterministicDigest` hashers.
+pub type BuildDeterministicDigest<D> =
+    DeterministicBuildHasher<BuildHasherDefault<DigestHasher<D>>>;
+
+impl<D> DigestHasher<D> {
+    pub fn new(inner: D) -> Self {
+        Self(inner)
+    }
+
+    pub fn as_inner(&self) -> &D {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> D {
+        self.0
+    }
+}
+
+impl<D: FixedOutput> DigestHasher<D> {
+    /// Returns the full output of the digest.
+    pub fn finalize(self) -> Output<D> {
+        self.0.finalize_fixed()
+    }
+}
+
+/// Returns the first 8 bytes of the output as a little-endian `u64`, padded with zeroes when the
+/// output is shorter.
+impl<D: Update + FixedOutput + Clone> Hasher for DigestHasher<D> {
+    fn finish(&self) -> u64 {
+        let output = self.finish_output();
+        let mut bytes = [0u8; 8];
+        let len = core::cmp::min(bytes.len(), output.len());
+        bytes[..len].copy_from_slice(&output[..len]);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+}
+
+impl<D: Update + FixedOutput + Clone> FixedOutputHasher for DigestHasher<D> {
+    type Output = Output<D>;
+
+    fn finish_output(&self) -> Output<D> {
+        self.0.clone().finalize_fixed()
+    }
+}
+
+impl<D: Update + FixedOutput + Clone + Default> HashAlgorithm for DigestHasher<D> {
+    fn new_hasher() -> Self {
+        Self::default()
+    }
+}
+
+impl<D: Update + FixedOutput + Clone> BoundaryIndependent for DigestHasher<D> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{DeterministicDigest, DigestHasher};
+    use crate::testing::assert_boundary_independent;
+    use ::digest::Digest;
+    use core::hash::{Hash, Hasher};
+    use sha2::Sha256;
+
+    #[test]
+    fn matches_digest() {
+        let mut hasher = DigestHasher::<Sha256>::default();
+        hasher.write(b"abc");
+        hasher.write(b"def");
+        let finish = hasher.finish();
+        let output = hasher.finalize();
+        assert_eq!(output, Sha256::digest(b"abcdef"));
+
+        let mut first = [0u8; 8];
+        first.copy_from_slice(&output[..8]);
+        assert_eq!(finish, u64::from_le_bytes(first));
+    }
+
+    #[test]
+    fn deterministic() {
+        let mut hasher = DeterministicDigest::<Sha256>::default();
+        (0x1337usize, -1isize).hash(&mut hasher);
+        let mut expected = [0u8; 16];
+        expected[..8].copy_from_slice(&0x1337u64.to_le_bytes());
+        expected[8..].copy_from_slice(&(-1i64).to_le_bytes());
+        assert_eq!(hasher.into_inner().finalize(), Sha256::digest(expected));
+    }
+
+    #[test]
+    fn boundary_independent() {
+        assert_boundary_independent::<DigestHasher<Sha256>>();
+    }
+}
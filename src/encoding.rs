@@ -0,0 +1,222 @@
+//! The byte encodings of integers that `DeterministicHasher` can write.
+//!
+//! `LittleEndian` is the default and writes the same bytes as `DeterministicHasher` always has.
+//! `BigEndian` writes network byte order, and `Varint` writes LEB128 varints, which take fewer bytes
+//! and cycles for small values on 8 and 16-bit targets.
+//!
+//! ```
+//! use core::hash::Hash;
+//! use crc::crc32::Hasher32;
+//! use deterministic_hash::encoding::Varint;
+//! use deterministic_hash::DeterministicHasher;
+//! let mut hasher = DeterministicHasher::<_, Varint>::with_encoding(crc::crc32::Digest::new(
+//!     crc::crc32::KOOPMAN,
+//! ));
+//! (0x1337 as usize).hash(&mut hasher);
+//! assert_eq!(hasher.as_inner().sum32(), 161258560);
+//! ```
+
+use core::hash::Hasher;
+
+/// The encoding of every integer type into bytes.
+///
+/// Implementations must only call `Hasher::write` on the state, as the integer methods of the
+/// state may use the native encoding.
+pub trait Encoding {
+    fn write_u8<H: Hasher>(state: &mut H, i: u8) {
+        state.write(&[i]);
+    }
+
+    fn write_u16<H: Hasher>(state: &mut H, i: u16);
+
+    fn write_u32<H: Hasher>(state: &mut H, i: u32);
+
+    fn write_u64<H: Hasher>(state: &mut H, i: u64);
+
+    fn write_u128<H: Hasher>(state: &mut H, i: u128);
+
+    fn write_i8<H: Hasher>(state: &mut H, i: i8) {
+        Self::write_u8(state, i as u8)
+    }
+
+    fn write_i16<H: Hasher>(state: &mut H, i: i16) {
+        Self::write_u16(state, i as u16)
+    }
+
+    fn write_i32<H: Hasher>(state: &mut H, i: i32) {
+        Self::write_u32(state, i as u32)
+    }
+
+    fn write_i64<H: Hasher>(state: &mut H, i: i64) {
+        Self::write_u64(state, i as u64)
+    }
+
+    fn write_i128<H: Hasher>(state: &mut H, i: i128) {
+        Self::write_u128(state, i as u128)
+    }
+
+    /// Writes a `usize` as a `u64`, regardless of the width of the target.
+    fn write_usize<H: Hasher>(state: &mut H, i: usize) {
+        Self::write_u64(state, i as u64)
+    }
+
+    /// Writes an `isize` sign-extended to an `i64`, regardless of the width of the target.
+    fn write_isize<H: Hasher>(state: &mut H, i: isize) {
+        Self::write_i64(state, i as i64)
+    }
+
+    /// Writes the length of a `str` or slice, as a `usize`.
+    fn write_length_prefix<H: Hasher>(state: &mut H, len: usize) {
+        Self::write_usize(state, len)
+    }
+}
+
+/// Writes integers as their little-endian bytes.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LittleEndian;
+
+/// Writes integers as their big-endian bytes, also known as network byte order.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BigEndian;
+
+/// Writes unsigned integers as LEB128 varints, and signed integers as zigzag-encoded LEB128
+/// varints. `u8` and `i8` are written as a single byte.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Varint;
+
+macro_rules! impl_fixed_encoding {
+    ($encoding:ty, $to_bytes:ident) => {
+        impl Encoding for $encoding {
+            fn write_u16<H: Hasher>(state: &mut H, i: u16) {
+                state.write(&i.$to_bytes())
+            }
+
+            fn write_u32<H: Hasher>(state: &mut H, i: u32) {
+                state.write(&i.$to_bytes())
+            }
+
+            fn write_u64<H: Hasher>(state: &mut H, i: u64) {
+                state.write(&i.$to_bytes())
+            }
+
+            fn write_u128<H: Hasher>(state: &mut H, i: u128) {
+                state.write(&i.$to_bytes())
+            }
+        }
+    };
+}
+
+impl_fixed_encoding!(LittleEndian, to_le_bytes);
+impl_fixed_encoding!(BigEndian, to_be_bytes);
+
+macro_rules! write_leb128 {
+    ($state:expr, $value:expr, $len:expr) => {{
+        let mut value = $value;
+        let mut buffer = [0u8; $len];
+        let mut len = 0;
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                buffer[len] = byte;
+                len += 1;
+                break;
+            }
+            buffer[len] = byte | 0x80;
+            len += 1;
+        }
+        $state.write(&buffer[..len]);
+    }};
+}
+
+impl Encoding for Varint {
+    fn write_u16<H: Hasher>(state: &mut H, i: u16) {
+        write_leb128!(state, i, 3)
+    }
+
+    fn write_u32<H: Hasher>(state: &mut H, i: u32) {
+        write_leb128!(state, i, 5)
+    }
+
+    fn write_u64<H: Hasher>(state: &mut H, i: u64) {
+        write_leb128!(state, i, 10)
+    }
+
+    fn write_u128<H: Hasher>(state: &mut H, i: u128) {
+        write_leb128!(state, i, 19)
+    }
+
+    fn write_i16<H: Hasher>(state: &mut H, i: i16) {
+        Self::write_u16(state, ((i << 1) ^ (i >> 15)) as u16)
+    }
+
+    fn write_i32<H: Hasher>(state: &mut H, i: i32) {
+        Self::write_u32(state, ((i << 1) ^ (i >> 31)) as u32)
+    }
+
+    fn write_i64<H: Hasher>(state: &mut H, i: i64) {
+        Self::write_u64(state, ((i << 1) ^ (i >> 63)) as u64)
+    }
+
+    fn write_i128<H: Hasher>(state: &mut H, i: i128) {
+        Self::write_u128(state, ((i << 1) ^ (i >> 127)) as u128)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{BigEndian, Encoding, LittleEndian, Varint};
+    use crate::testing::Bytes;
+    use crate::DeterministicHasher;
+    use core::hash::Hash;
+    use std::vec;
+    use std::vec::Vec;
+
+    fn bytes<E: Encoding, T: Hash>(value: T) -> Vec<u8> {
+        let mut hasher = DeterministicHasher::<Bytes, E>::default();
+        value.hash(&mut hasher);
+        hasher.into_inner().0
+    }
+
+    #[test]
+    fn little_endian() {
+        assert_eq!(bytes::<LittleEndian, _>(0x0102u16), vec![0x02, 0x01]);
+        assert_eq!(
+            bytes::<LittleEndian, _>(-2isize),
+            vec![0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+        );
+        assert_eq!(
+            bytes::<LittleEndian, _>((0x1337usize, 'a', -1i8)),
+            bytes::<LittleEndian, _>((0x1337u64, 0x61u32, 0xffu8))
+        );
+    }
+
+    #[test]
+    fn big_endian() {
+        assert_eq!(bytes::<BigEndian, _>(0x0102u16), vec![0x01, 0x02]);
+        assert_eq!(
+            bytes::<BigEndian, _>(0x1337usize),
+            vec![0, 0, 0, 0, 0, 0, 0x13, 0x37]
+        );
+        assert_eq!(bytes::<BigEndian, _>(-1isize), vec![0xff; 8]);
+    }
+
+    #[test]
+    fn varint() {
+        assert_eq!(bytes::<Varint, _>(0u32), vec![0x00]);
+        assert_eq!(bytes::<Varint, _>(300u16), vec![0xac, 0x02]);
+        assert_eq!(bytes::<Varint, _>(0x1337usize), vec![0xb7, 0x26]);
+        assert_eq!(bytes::<Varint, _>(0xffu8), vec![0xff]);
+        assert_eq!(bytes::<Varint, _>(-1i8), vec![0xff]);
+        assert_eq!(bytes::<Varint, _>(-1isize), vec![0x01]);
+        assert_eq!(bytes::<Varint, _>(1i32), vec![0x02]);
+        assert_eq!(bytes::<Varint, _>(i64::MIN), {
+            let mut expected = vec![0xff; 9];
+            expected.push(0x01);
+            expected
+        });
+        assert_eq!(bytes::<Varint, _>(u128::MAX).len(), 19);
+    }
+}
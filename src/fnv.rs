@@ -0,0 +1,190 @@
+//! The FNV-1a hash function, in its 32 and 64-bit variants.
+//!
+//! FNV-1a is tiny and fast for short inputs, but offers no protection against collisions that are
+//! crafted on purpose.
+//!
+//! ```
+//! use core::hash::{Hash, Hasher};
+//! use deterministic_hash::fnv::DeterministicFnv1a64;
+//! let mut hasher = DeterministicFnv1a64::default();
+//! (0x1337 as usize).hash(&mut hasher);
+//! assert_eq!(hasher.finish(), 0x41b0fe56e946b8c7);
+//! ```
+
+use crate::{
+    BoundaryIndependent, DeterministicBuildHasher, DeterministicHasher, FixedOutputHasher,
+    HashAlgorithm,
+};
+use core::hash::{BuildHasherDefault, Hasher};
+
+/// The 32-bit FNV-1a hasher.
+#[derive(Clone, Debug)]
+pub struct Fnv1a32(u32);
+
+/// The 64-bit FNV-1a hasher.
+#[derive(Clone, Debug)]
+pub struct Fnv1a64(u64);
+
+/// `DeterministicHasher` around the 32-bit FNV-1a hasher.
+pub type DeterministicFnv1a32 = DeterministicHasher<Fnv1a32>;
+
+/// `DeterministicBuildHasher` that builds `DeterministicFnv1a32` hashers.
+pub type BuildDeterministicFnv1a32 = DeterministicBuildHasher<BuildHasherDefault<Fnv1a32>>;
+
+/// `DeterministicHasher` around the 64-bit FNV-1a hasher.
+pub type DeterministicFnv1a64 = DeterministicHasher<Fnv1a64>;
+
+/// `DeterministicBuildHasher` that builds `DeterministicFnv1a64` hashers.
+pub type BuildDeterministicFnv1a64 = DeterministicBuildHasher<BuildHasherDefault<Fnv1a64>>;
+
+impl Fnv1a32 {
+    const OFFSET_BASIS: u32 = 0x811c_9dc5;
+    const PRIME: u32 = 0x0100_0193;
+
+    pub const fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    /// Returns the 32-bit hash of the bytes written so far.
+    pub fn finish32(&self) -> u32 {
+        self.0
+    }
+}
+
+impl Fnv1a64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    pub const fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+}
+
+impl Default for Fnv1a32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Default for Fnv1a64 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the 32-bit hash zero-extended to a `u64`.
+impl Hasher for Fnv1a32 {
+    fn finish(&self) -> u64 {
+        self.0 as u64
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u32).wrapping_mul(Self::PRIME);
+        }
+    }
+}
+
+impl Hasher for Fnv1a64 {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(Self::PRIME);
+        }
+    }
+}
+
+impl FixedOutputHasher for Fnv1a32 {
+    type Output = u32;
+
+    fn finish_output(&self) -> u32 {
+        self.finish32()
+    }
+}
+
+impl FixedOutputHasher for Fnv1a64 {
+    type Output = u64;
+
+    fn finish_output(&self) -> u64 {
+        self.0
+    }
+}
+
+impl HashAlgorithm for Fnv1a32 {
+    fn new_hasher() -> Self {
+        Self::new()
+    }
+}
+
+impl BoundaryIndependent for Fnv1a32 {}
+
+impl HashAlgorithm for Fnv1a64 {
+    fn new_hasher() -> Self {
+        Self::new()
+    }
+}
+
+impl BoundaryIndependent for Fnv1a64 {}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{BuildDeterministicFnv1a64, DeterministicFnv1a64, Fnv1a32, Fnv1a64};
+    use crate::testing::assert_boundary_independent;
+    use core::hash::{BuildHasher, Hash, Hasher};
+    use std::collections::HashMap;
+
+    const VECTORS: [(&[u8], u32, u64); 3] = [
+        (b"", 0x811c9dc5, 0xcbf29ce484222325),
+        (b"a", 0xe40c292c, 0xaf63dc4c8601ec8c),
+        (b"foobar", 0xbf9cf968, 0x85944171f73967e8),
+    ];
+
+    #[test]
+    fn reference_vectors() {
+        for (input, expected32, expected64) in VECTORS.iter() {
+            let mut fnv32 = Fnv1a32::new();
+            let mut fnv64 = Fnv1a64::new();
+            for byte in input.iter() {
+                fnv32.write(&[*byte]);
+                fnv64.write(&[*byte]);
+            }
+            assert_eq!(fnv32.finish32(), *expected32);
+            assert_eq!(fnv32.finish(), *expected32 as u64);
+            assert_eq!(fnv64.finish(), *expected64);
+        }
+    }
+
+    #[test]
+    fn build_hasher() {
+        let build_hasher = BuildDeterministicFnv1a64::default();
+        let mut hasher = DeterministicFnv1a64::default();
+        (0x1337usize, "abc").hash(&mut hasher);
+        assert_eq!(build_hasher.hash_one((0x1337usize, "abc")), hasher.finish());
+
+        let mut map = HashMap::with_hasher(build_hasher);
+        map.insert("abc", 1);
+        assert_eq!(map.get("abc"), Some(&1));
+    }
+
+    #[test]
+    fn hash_one() {
+        let mut hasher = DeterministicFnv1a64::default();
+        (0x1337usize, "abc").hash(&mut hasher);
+        assert_eq!(
+            crate::hash_one::<Fnv1a64, _>(&(0x1337usize, "abc")),
+            hasher.finish()
+        );
+        assert_eq!(crate::hash_bytes::<Fnv1a32, _>(b"foobar"), 0xbf9cf968);
+    }
+
+    #[test]
+    fn boundary_independent() {
+        assert_boundary_independent::<Fnv1a32>();
+        assert_boundary_independent::<Fnv1a64>();
+    }
+}
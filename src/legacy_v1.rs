@@ -0,0 +1,158 @@
+//! Reproduces the output of `deterministic-hash` 1.0.
+//!
+//! Version 1.0 hashed `isize` by zero-extending its `usize` representation, so negative values
+//! hashed differently on 16, 32 and 64-bit targets. Use this hasher only to verify hashes that were
+//! stored with 1.0 while migrating them to `crate::DeterministicHasher`.
+//!
+//! ```
+//! use core::hash::Hash;
+//! use crc::crc32::Hasher32;
+//! let mut hasher = deterministic_hash::legacy_v1::DeterministicHasher::new(
+//!     crc::crc32::Digest::new(crc::crc32::KOOPMAN),
+//! );
+//! (0x1337 as usize).hash(&mut hasher);
+//! assert_eq!(hasher.as_inner().sum32(), 2482448842);
+//! ```
+
+use crate::FixedOutputHasher;
+use core::hash::Hasher;
+
+/// Wrapper around any hasher that produces the same bytes as `DeterministicHasher` 1.0 did on the
+/// current target.
+pub struct DeterministicHasher<T: Hasher>(crate::DeterministicHasher<T>);
+
+impl<T: Hasher> DeterministicHasher<T> {
+    pub fn new(inner: T) -> Self {
+        Self(crate::DeterministicHasher::new(inner))
+    }
+
+    pub fn as_inner(&self) -> &T {
+        self.0.as_inner()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0.into_inner()
+    }
+}
+
+impl<T: Hasher> core::hash::Hasher for DeterministicHasher<T> {
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes);
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.0.write_u8(i)
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.0.write_u16(i)
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.0.write_u32(i)
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.0.write_u64(i)
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.0.write_u128(i)
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.0.write_usize(i)
+    }
+
+    fn write_i8(&mut self, i: i8) {
+        self.0.write_i8(i)
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.0.write_i16(i)
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.0.write_i32(i)
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.0.write_i64(i)
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.0.write_i128(i)
+    }
+
+    /// Zero-extends the native representation, which is what makes this hasher target dependent.
+    fn write_isize(&mut self, i: isize) {
+        self.0.write_usize(i as usize)
+    }
+
+    /// Version 1.0 left the length prefix to `core`, which writes a `usize`.
+    #[cfg(feature = "nightly")]
+    fn write_length_prefix(&mut self, len: usize) {
+        self.0.write_usize(len)
+    }
+
+    /// Version 1.0 left strings to `core`, which terminates them with `0xFF`.
+    #[cfg(feature = "nightly")]
+    fn write_str(&mut self, s: &str) {
+        self.0.write(s.as_bytes());
+        self.0.write_u8(0xFF);
+    }
+}
+
+impl<T: FixedOutputHasher> FixedOutputHasher for DeterministicHasher<T> {
+    type Output = T::Output;
+
+    fn finish_output(&self) -> T::Output {
+        self.0.finish_output()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DeterministicHasher;
+    use core::hash::{Hash, Hasher};
+    use crc::crc32::{Digest, Hasher32, KOOPMAN};
+
+    #[test]
+    fn negative_isize_is_zero_extended() {
+        let mut legacy = DeterministicHasher::new(Digest::new(KOOPMAN));
+        (-1isize).hash(&mut legacy);
+
+        let mut expected = crate::DeterministicHasher::new(Digest::new(KOOPMAN));
+        expected.write_u64(usize::MAX as u64);
+
+        assert_eq!(legacy.as_inner().sum32(), expected.as_inner().sum32());
+    }
+
+    #[test]
+    fn non_negative_isize_is_unchanged() {
+        let mut legacy = DeterministicHasher::new(Digest::new(KOOPMAN));
+        (0x1337isize, 42u16, &b"abc"[..]).hash(&mut legacy);
+
+        let mut current = crate::DeterministicHasher::new(Digest::new(KOOPMAN));
+        (0x1337isize, 42u16, &b"abc"[..]).hash(&mut current);
+
+        assert_eq!(legacy.as_inner().sum32(), current.as_inner().sum32());
+    }
+
+    #[test]
+    #[cfg(feature = "nightly")]
+    fn str_is_terminated() {
+        let mut legacy = DeterministicHasher::new(Digest::new(KOOPMAN));
+        "abc".hash(&mut legacy);
+
+        let mut expected = crate::DeterministicHasher::new(Digest::new(KOOPMAN));
+        expected.write(b"abc");
+        expected.write_u8(0xFF);
+
+        assert_eq!(legacy.as_inner().sum32(), expected.as_inner().sum32());
+    }
+}
@@ -1,22 +1,119 @@
-//! Tiny Rust library to create deterministic hashes regardless of architecture. This library is `no-std` compatible and uses no allocations or dependencies.
+//! Tiny Rust library to create deterministic hashes regardless of architecture. This library is `no-std` compatible and uses no allocations or dependencies by default.
 //!
 //! The default `core::hash::Hasher` implementation ensures a platform dependant hashing of datastructures that use `#[derive(Hash)]`. Most notably by:
 //! * using `to_ne_bytes` for `u{8,16,32,64,128}`.
 //! * using the native bytelength of `usize`.
 //!
-//! The `DeterministicHasher` of this library forces the use of `to_le_bytes` and casts `usize` to `u64` regardless of your platform. Hence the hasher will be less efficient, but will be deterministic when using the same library in different architecture contexts. I use a common dataprotocol library both on ARM embedded systems, wasm and x64.
+//! The `DeterministicHasher` of this library forces the use of `to_le_bytes` and casts `usize` to `u64` (and sign-extends `isize` to `i64`) regardless of your platform. Hence the hasher will be less efficient, but will be deterministic when using the same library in different architecture contexts. I use a common dataprotocol library both on ARM embedded systems, wasm and x64.
+//!
+//! `DeterministicHasher` writes integers little-endian by default. Its `Encoding` type parameter selects big-endian network order or LEB128 varints instead, see the `encoding` module.
+//!
+//! Wrap the hasher in `checked::CheckedUsizeHasher` on a 64-bit host to reject `usize` and `isize` values that a 32 or 16-bit target could never produce. Wrap it in `strict::StrictHasher` to detect `Hash` impls that write native slices or pointer addresses.
+//!
+//! With the `nightly` feature, `DeterministicHasher` also implements `Hasher::write_length_prefix` and `Hasher::write_str`, so lengths are written as a `u64` and strings as their length followed by their bytes, instead of whatever `core` does. On stable Rust, `StableHash` writes the same bytes for strings and slices.
+//!
+//! Enable the `map` feature for `DeterministicHashMap` and `DeterministicHashSet` in the `map` module, which iterate in insertion order rather than in an order that depends on the target.
 //!
 //! From any hasher make it deterministic by inserting `DeterministicHasher` in between:
 //! ```
 //! let hasher = crc::crc32::Digest::new(crc::crc32::KOOPMAN);
 //! let hasher = deterministic_hash::DeterministicHasher::new(hasher);
 //! ```
+//!
+//! `core` hashes slices of integers like `[u32]` and `[usize]` with a single write of their native memory, which bypasses `DeterministicHasher`, and may change how it writes strings, lengths and enum discriminants between Rust releases. The `StableHash` trait of this library writes values with a documented and versioned byte encoding instead, and works with any hasher. Enable the `alloc` feature to implement it for collections. Enable the `derive` feature for `#[derive(StableHash)]`.
+//!
+//! The library also ships `no-std` implementations of common non-cryptographic hash functions, each behind its own feature and most with a `Deterministic*` alias that wraps it in `DeterministicHasher` and a `BuildDeterministic*` alias to use it in a `HashMap`:
+//! * `crc`: CRCs of any algorithm of the CRC RevEng catalogue, with bitwise, table-driven and slice-by-8 backends.
+//! * `fnv`: FNV-1a in its 32 and 64-bit variants.
+//! * `murmur3`: MurmurHash3 `x86_32` and `x64_128`.
+//! * `siphash`: SipHash-1-3 and SipHash-2-4 with explicit keys. With zero keys, SipHash-1-3 reproduces `std`'s `DefaultHasher` on little-endian 64-bit platforms.
+//! * `xxhash`: xxHash32 and xxHash64.
+//! * `xxh3`: XXH3 in its 64 and 128-bit variants.
+//!
+//! Enable the `digest` feature to hash with any RustCrypto digest, like SHA-256, SHA-3 or BLAKE2, through `digest::DigestHasher`, which also returns the full output of the digest. Enable the `blake3` feature for BLAKE3 in its regular, keyed and key derivation modes, with an extendable output of any length.
+//!
+//! All built-in algorithms implement `FixedOutputHasher`, so `DeterministicHasher::finish_output` returns their output at its natural width, like a `u32` for 32-bit CRCs or a `u128` for 128-bit hashes, rather than the `u64` of `Hasher::finish`.
+//!
+//! The `hash_one`, `stable_hash_one` and `hash_bytes` functions hash a value or bytes in a single call, with any algorithm that implements `HashAlgorithm` as a type parameter.
+//!
+//! Hashing a large derived struct results in many small writes. `DeterministicHasher::new_buffered` collects them in a buffer on the stack and passes them to the inner hasher in blocks, with the same output. Run `cargo bench --features digest,siphash,xxhash` to compare both.
+//!
+//! All built-in algorithms implement `BoundaryIndependent`: their output only depends on the written bytes, not on how a `Hash` impl split them into writes. Wrap any other hasher in a `buffered::BufferedHasher` to get the same guarantee.
+//!
+//! When two hashes differ, wrap the hasher in a `recording::RecordingHasher` on both sides. It records every call to the hasher into a transcript with a compact binary format, which can be decoded, printed and replayed into another hasher on the host.
+//!
+//! In tests, `assert_hash_eq!` (with the `alloc` feature) compares two values by the bytes they hash to, and reports the first call to the hasher that differs, like `event 17 at byte 120: write_usize(3) vs write_usize(4)`.
+//!
+//! Enable the `snapshot` feature for `assert_hash_stable!`, which pins the hashes of a value in a checked-in snapshot file and fails when they change, for example after a refactor or a Rust upgrade. Set `DETERMINISTIC_HASH_UPDATE_SNAPSHOTS=1` to write new snapshots.
+//!
+//! Call `self_check::<A>()` at boot or in an integration test to verify that this compiler and target hash a built-in corpus of values to its canonical byte stream, and that the algorithm `A` returns its compiled-in output for it.
+//!
+//! Enable the `conformance` feature to run a generated suite of edge-case inputs through `DeterministicHasher` with your own inner hasher, like a hardware CRC. `conformance::run` emits a binary report of the outputs, which `conformance::verify` checks byte for byte on another target.
 
 #![no_std]
-use core::hash::Hasher;
+#![cfg_attr(feature = "nightly", feature(hasher_prefixfree_extras))]
+use core::fmt::Debug;
+use core::hash::{BuildHasher, Hash, Hasher};
+use core::marker::PhantomData;
+use encoding::LittleEndian;
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "snapshot")]
+extern crate std;
+
+#[cfg(feature = "blake3")]
+pub mod blake3;
+#[cfg(any(
+    feature = "murmur3",
+    feature = "siphash",
+    feature = "xxhash",
+    feature = "xxh3"
+))]
+mod block;
+pub mod buffered;
+pub mod checked;
+#[cfg(feature = "conformance")]
+pub mod conformance;
+#[cfg(feature = "crc")]
+pub mod crc;
+#[cfg(feature = "digest")]
+pub mod digest;
+pub mod encoding;
+#[cfg(feature = "fnv")]
+pub mod fnv;
+pub mod legacy_v1;
+#[cfg(feature = "map")]
+pub mod map;
+#[cfg(feature = "murmur3")]
+pub mod murmur3;
+pub mod recording;
+pub mod self_check;
+#[cfg(feature = "siphash")]
+pub mod siphash;
+#[cfg(feature = "snapshot")]
+pub mod snapshot;
+pub mod stable;
+pub mod strict;
+#[cfg(test)]
+mod testing;
+#[cfg(feature = "xxh3")]
+pub mod xxh3;
+#[cfg(feature = "xxhash")]
+pub mod xxhash;
+
+pub use encoding::Encoding;
+pub use self_check::self_check;
+pub use stable::StableHash;
+
+/// Derives `StableHash`, see the `deterministic-hash-derive` crate for the supported attributes.
+#[cfg(feature = "derive")]
+pub use deterministic_hash_derive::StableHash;
 
 /// Wrapper around any hasher to make it deterministic.
 ///
+/// Integers are written with the encoding `E`, which defaults to `encoding::LittleEndian`.
+///
 /// ```
 /// use core::hash::Hash;
 /// use crc::crc32::Hasher32;
@@ -25,11 +122,18 @@ use core::hash::Hasher;
 /// (0x1337 as usize).hash(&mut hasher);
 /// assert_eq!(hasher.as_inner().sum32(), 2482448842);
 /// ```
-pub struct DeterministicHasher<T: Hasher>(T);
+pub struct DeterministicHasher<T: Hasher, E: Encoding = LittleEndian>(T, PhantomData<E>);
 
 impl<T: Hasher> DeterministicHasher<T> {
     pub fn new(inner: T) -> Self {
-        Self(inner)
+        Self::with_encoding(inner)
+    }
+}
+
+impl<T: Hasher, E: Encoding> DeterministicHasher<T, E> {
+    /// Wraps the hasher, writing integers with the encoding `E`.
+    pub fn with_encoding(inner: T) -> Self {
+        Self(inner, PhantomData)
     }
 
     pub fn as_inner(&self) -> &T {
@@ -41,8 +145,124 @@ impl<T: Hasher> DeterministicHasher<T> {
     }
 }
 
+impl<T: Hasher + Default, E: Encoding> Default for DeterministicHasher<T, E> {
+    fn default() -> Self {
+        Self::with_encoding(T::default())
+    }
+}
+
+/// A hasher that returns its output at its natural width, such as a `u32` for 32-bit CRCs, a `u128`
+/// for 128-bit hashes or a byte array for cryptographic digests.
+pub trait FixedOutputHasher: Hasher {
+    type Output: Clone + Eq + Debug;
+
+    /// Returns the output for the bytes written so far.
+    fn finish_output(&self) -> Self::Output;
+}
+
+/// A hasher whose output only depends on the concatenation of the written bytes, and not on how
+/// those bytes were split into calls to `Hasher::write`.
+///
+/// All built-in algorithms are boundary independent. Any other hasher can be made boundary
+/// independent by wrapping it in a `buffered::BufferedHasher`.
+pub trait BoundaryIndependent: Hasher {}
+
+/// A hash algorithm that can be constructed without any parameters, such that it can be passed as
+/// a type parameter.
+///
+/// Keyed and seeded algorithms use a key or seed of zero.
+pub trait HashAlgorithm: FixedOutputHasher {
+    fn new_hasher() -> Self;
+}
+
+/// Hashes the value with the algorithm `A`, through a `DeterministicHasher`.
+pub fn hash_one<A: HashAlgorithm, T: Hash + ?Sized>(value: &T) -> A::Output {
+    let mut hasher = DeterministicHasher::new(A::new_hasher());
+    value.hash(&mut hasher);
+    hasher.finish_output()
+}
+
+/// Hashes the value with the algorithm `A`, using the encoding of `StableHash`.
+pub fn stable_hash_one<A: HashAlgorithm, T: StableHash + ?Sized>(value: &T) -> A::Output {
+    let mut hasher = A::new_hasher();
+    value.stable_hash(&mut hasher);
+    hasher.finish_output()
+}
+
+/// Hashes the bytes with the algorithm `A`, without any length prefix.
+pub fn hash_bytes<A: HashAlgorithm, B: AsRef<[u8]> + ?Sized>(bytes: &B) -> A::Output {
+    let mut hasher = A::new_hasher();
+    hasher.write(bytes.as_ref());
+    hasher.finish_output()
+}
+
+/// Integers are written as a single call to `Hasher::write`, so the output only depends on the
+/// encoded bytes.
+impl<T: BoundaryIndependent, E: Encoding> BoundaryIndependent for DeterministicHasher<T, E> {}
+
+impl<T: FixedOutputHasher, E: Encoding> FixedOutputHasher for DeterministicHasher<T, E> {
+    type Output = T::Output;
+
+    fn finish_output(&self) -> T::Output {
+        self.0.finish_output()
+    }
+}
+
+/// Builds a `DeterministicHasher` around every hasher built by `B`, for use in `HashMap` and
+/// `HashSet`.
+///
+/// Every algorithm module also has a `BuildDeterministic*` alias that builds its hasher with
+/// `Default`. Note that a deterministic hasher is not enough for a deterministic iteration order:
+/// `std` and `hashbrown` also lay out their buckets according to the SIMD group width of the
+/// target.
+///
+/// ```
+/// use core::hash::BuildHasher;
+/// use deterministic_hash::DeterministicBuildHasher;
+/// struct Koopman;
+/// impl BuildHasher for Koopman {
+///     type Hasher = crc::crc32::Digest;
+///     fn build_hasher(&self) -> Self::Hasher {
+///         crc::crc32::Digest::new(crc::crc32::KOOPMAN)
+///     }
+/// }
+/// let build_hasher = DeterministicBuildHasher::new(Koopman);
+/// assert_eq!(build_hasher.hash_one(0x1337 as usize), 2482448842);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DeterministicBuildHasher<B: BuildHasher, E: Encoding = LittleEndian>(B, PhantomData<E>);
+
+impl<B: BuildHasher> DeterministicBuildHasher<B> {
+    pub fn new(inner: B) -> Self {
+        Self::with_encoding(inner)
+    }
+}
+
+impl<B: BuildHasher, E: Encoding> DeterministicBuildHasher<B, E> {
+    /// Wraps the builder, building hashers that write integers with the encoding `E`.
+    pub fn with_encoding(inner: B) -> Self {
+        Self(inner, PhantomData)
+    }
+
+    pub fn as_inner(&self) -> &B {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> B {
+        self.0
+    }
+}
+
+impl<B: BuildHasher, E: Encoding> BuildHasher for DeterministicBuildHasher<B, E> {
+    type Hasher = DeterministicHasher<B::Hasher, E>;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        DeterministicHasher::with_encoding(self.0.build_hasher())
+    }
+}
+
 /// Implementation of hasher that forces all bytes written to be platform agnostic.
-impl<T: Hasher> core::hash::Hasher for DeterministicHasher<T> {
+impl<T: Hasher, E: Encoding> core::hash::Hasher for DeterministicHasher<T, E> {
     fn finish(&self) -> u64 {
         self.0.finish()
     }
@@ -52,58 +272,104 @@ impl<T: Hasher> core::hash::Hasher for DeterministicHasher<T> {
     }
 
     fn write_u8(&mut self, i: u8) {
-        self.write(&i.to_le_bytes())
+        E::write_u8(&mut self.0, i)
     }
 
     fn write_u16(&mut self, i: u16) {
-        self.write(&i.to_le_bytes())
+        E::write_u16(&mut self.0, i)
     }
 
     fn write_u32(&mut self, i: u32) {
-        self.write(&i.to_le_bytes())
+        E::write_u32(&mut self.0, i)
     }
 
     fn write_u64(&mut self, i: u64) {
-        self.write(&i.to_le_bytes())
+        E::write_u64(&mut self.0, i)
     }
 
     fn write_u128(&mut self, i: u128) {
-        self.write(&i.to_le_bytes())
+        E::write_u128(&mut self.0, i)
     }
 
     fn write_usize(&mut self, i: usize) {
-        self.write(&(i as u64).to_le_bytes())
+        E::write_usize(&mut self.0, i)
     }
 
     fn write_i8(&mut self, i: i8) {
-        self.write_u8(i as u8)
+        E::write_i8(&mut self.0, i)
     }
 
     fn write_i16(&mut self, i: i16) {
-        self.write_u16(i as u16)
+        E::write_i16(&mut self.0, i)
     }
 
     fn write_i32(&mut self, i: i32) {
-        self.write_u32(i as u32)
+        E::write_i32(&mut self.0, i)
     }
 
     fn write_i64(&mut self, i: i64) {
-        self.write_u64(i as u64)
+        E::write_i64(&mut self.0, i)
     }
 
     fn write_i128(&mut self, i: i128) {
-        self.write_u128(i as u128)
+        E::write_i128(&mut self.0, i)
     }
 
     fn write_isize(&mut self, i: isize) {
-        self.write_usize(i as usize)
+        E::write_isize(&mut self.0, i)
+    }
+
+    #[cfg(feature = "nightly")]
+    fn write_length_prefix(&mut self, len: usize) {
+        E::write_length_prefix(&mut self.0, len)
+    }
+
+    /// Writes the length prefix followed by the bytes, without the `0xFF` terminator of `core`.
+    #[cfg(feature = "nightly")]
+    fn write_str(&mut self, s: &str) {
+        self.write_length_prefix(s.len());
+        self.write(s.as_bytes());
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::DeterministicHasher;
+    use core::hash::{Hash, Hasher};
+    use crc::crc32::{Digest, Hasher32, KOOPMAN};
+
+    fn crc32<T: Hash>(value: T) -> u32 {
+        let mut hasher = DeterministicHasher::new(Digest::new(KOOPMAN));
+        value.hash(&mut hasher);
+        hasher.as_inner().sum32()
+    }
+
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn isize_is_sign_extended() {
+        assert_eq!(crc32(-1isize), crc32(-1i64));
+        assert_eq!(crc32(isize::MIN), crc32(isize::MIN as i64));
+        assert_eq!(crc32(0x1337isize), crc32(0x1337usize));
+
+        let mut hasher = DeterministicHasher::new(Digest::new(KOOPMAN));
+        hasher.write(&[0xFF; 8]);
+        assert_eq!(crc32(-1isize), hasher.as_inner().sum32());
+    }
+
+    #[test]
+    #[cfg(feature = "nightly")]
+    fn prefix_free_extras_match_stable_hash() {
+        use crate::StableHash;
+
+        let value = ("abc", &[1u8, 2][..], ["", "d"]);
+        let mut hasher = DeterministicHasher::new(Digest::new(KOOPMAN));
+        value.hash(&mut hasher);
+        let mut stable = Digest::new(KOOPMAN);
+        value.stable_hash(&mut stable);
+        assert_eq!(hasher.as_inner().sum32(), stable.sum32());
+    }
 }
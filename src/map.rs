@@ -0,0 +1,460 @@
+//! Hash maps and sets whose iteration order is identical on every target.
+//!
+//! The iteration order of `std` and `hashbrown` maps depends on their bucket layout, which depends
+//! on the SIMD group width of the target even when the hasher is deterministic.
+//! `DeterministicHashMap` and `DeterministicHashSet` iterate in insertion order instead, so the
+//! order only depends on the inserted keys and the history of insertions and removals.
+//!
+//! Inserting a key that is already present keeps its position. Removing a key shifts all later
+//! entries, which takes time linear in the number of entries.
+//!
+//! ```
+//! use deterministic_hash::map::DeterministicHashMap;
+//! let mut map = DeterministicHashMap::new();
+//! map.insert("delta", 4);
+//! map.insert("alpha", 1);
+//! map.insert("charlie", 3);
+//! map.remove("alpha");
+//! map.insert("bravo", 2);
+//! assert!(map.keys().eq(["delta", "charlie", "bravo"].iter()));
+//! ```
+
+use crate::fnv::BuildDeterministicFnv1a64;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+use core::iter::FromIterator;
+use hashbrown::HashTable;
+
+#[derive(Clone)]
+struct Bucket<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+}
+
+/// A hash map that iterates in insertion order.
+#[derive(Clone)]
+pub struct DeterministicHashMap<K, V, S = BuildDeterministicFnv1a64> {
+    entries: Vec<Bucket<K, V>>,
+    /// The position of every entry in `entries`, by the hash of its key.
+    indices: HashTable<usize>,
+    build_hasher: S,
+}
+
+/// A hash set that iterates in insertion order.
+#[derive(Clone)]
+pub struct DeterministicHashSet<T, S = BuildDeterministicFnv1a64> {
+    map: DeterministicHashMap<T, (), S>,
+}
+
+impl<K, V> DeterministicHashMap<K, V> {
+    pub fn new() -> Self {
+        Self::with_hasher(Default::default())
+    }
+}
+
+impl<K, V, S> DeterministicHashMap<K, V, S> {
+    pub fn with_hasher(build_hasher: S) -> Self {
+        Self {
+            entries: Vec::new(),
+            indices: HashTable::new(),
+            build_hasher,
+        }
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, build_hasher: S) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            indices: HashTable::with_capacity(capacity),
+            build_hasher,
+        }
+    }
+
+    pub fn hasher(&self) -> &S {
+        &self.build_hasher
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.indices.clear();
+    }
+
+    /// Returns the entries in insertion order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter(self.entries.iter())
+    }
+
+    /// Returns the entries in insertion order, with mutable values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut(self.entries.iter_mut())
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> + '_ {
+        self.iter().map(|(_, value)| value)
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> DeterministicHashMap<K, V, S> {
+    fn find<Q>(&self, hash: u64, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        let entries = &self.entries;
+        self.indices
+            .find(hash, |&i| entries[i].key.borrow() == key)
+            .copied()
+    }
+
+    /// Inserts the value, returning the previous value of the key if it was already present, in
+    /// which case the key keeps its position.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let hash = self.build_hasher.hash_one(&key);
+        if let Some(i) = self.find(hash, &key) {
+            return Some(core::mem::replace(&mut self.entries[i].value, value));
+        }
+
+        let entries = &self.entries;
+        self.indices
+            .insert_unique(hash, entries.len(), |&i| entries[i].hash);
+        self.entries.push(Bucket { hash, key, value });
+        None
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let i = self.find(self.build_hasher.hash_one(key), key)?;
+        Some(&self.entries[i].value)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let i = self.find(self.build_hasher.hash_one(key), key)?;
+        Some(&mut self.entries[i].value)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Removes the key, shifting all later entries to keep the insertion order.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    /// Removes the key, shifting all later entries to keep the insertion order.
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.build_hasher.hash_one(key);
+        let entries = &self.entries;
+        let (removed, _) = self
+            .indices
+            .find_entry(hash, |&i| entries[i].key.borrow() == key)
+            .ok()?
+            .remove();
+        for i in self.indices.iter_mut() {
+            if *i > removed {
+                *i -= 1;
+            }
+        }
+        let bucket = self.entries.remove(removed);
+        Some((bucket.key, bucket.value))
+    }
+}
+
+impl<K, V, S: Default> Default for DeterministicHashMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for DeterministicHashMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Maps are equal when they contain the same entries, regardless of their order.
+impl<K: Hash + Eq, V: PartialEq, S: BuildHasher> PartialEq for DeterministicHashMap<K, V, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<K: Hash + Eq, V: Eq, S: BuildHasher> Eq for DeterministicHashMap<K, V, S> {}
+
+impl<K: Hash + Eq, V, S: BuildHasher> Extend<(K, V)> for DeterministicHashMap<K, V, S> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> FromIterator<(K, V)>
+    for DeterministicHashMap<K, V, S>
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+/// Iterator over the entries of a `DeterministicHashMap`, in insertion order.
+pub struct Iter<'a, K, V>(core::slice::Iter<'a, Bucket<K, V>>);
+
+/// Iterator over the entries of a `DeterministicHashMap` with mutable values, in insertion order.
+pub struct IterMut<'a, K, V>(core::slice::IterMut<'a, Bucket<K, V>>);
+
+/// Owning iterator over the entries of a `DeterministicHashMap`, in insertion order.
+pub struct IntoIter<K, V>(alloc::vec::IntoIter<Bucket<K, V>>);
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|bucket| (&bucket.key, &bucket.value))
+    }
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|bucket| (&bucket.key, &mut bucket.value))
+    }
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|bucket| (bucket.key, bucket.value))
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a DeterministicHashMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V, S> IntoIterator for DeterministicHashMap<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.entries.into_iter())
+    }
+}
+
+impl<T> DeterministicHashSet<T> {
+    pub fn new() -> Self {
+        Self::with_hasher(Default::default())
+    }
+}
+
+impl<T, S> DeterministicHashSet<T, S> {
+    pub fn with_hasher(build_hasher: S) -> Self {
+        Self {
+            map: DeterministicHashMap::with_hasher(build_hasher),
+        }
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, build_hasher: S) -> Self {
+        Self {
+            map: DeterministicHashMap::with_capacity_and_hasher(capacity, build_hasher),
+        }
+    }
+
+    pub fn hasher(&self) -> &S {
+        self.map.hasher()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear()
+    }
+
+    /// Returns the values in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.map.keys()
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher> DeterministicHashSet<T, S> {
+    /// Inserts the value, returning whether it was not present yet.
+    pub fn insert(&mut self, value: T) -> bool {
+        self.map.insert(value, ()).is_none()
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(value)
+    }
+
+    /// Removes the value, shifting all later values to keep the insertion order.
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.remove(value).is_some()
+    }
+}
+
+impl<T, S: Default> Default for DeterministicHashSet<T, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<T: fmt::Debug, S> fmt::Debug for DeterministicHashSet<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// Sets are equal when they contain the same values, regardless of their order.
+impl<T: Hash + Eq, S: BuildHasher> PartialEq for DeterministicHashSet<T, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher> Eq for DeterministicHashSet<T, S> {}
+
+impl<T: Hash + Eq, S: BuildHasher> Extend<T> for DeterministicHashSet<T, S> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.map.extend(iter.into_iter().map(|value| (value, ())));
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher + Default> FromIterator<T> for DeterministicHashSet<T, S> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::default();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T, S> IntoIterator for DeterministicHashSet<T, S> {
+    type Item = T;
+    type IntoIter = core::iter::Map<IntoIter<T, ()>, fn((T, ())) -> T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.into_iter().map(|(value, ())| value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DeterministicHashMap, DeterministicHashSet};
+    use alloc::vec::Vec;
+
+    const WORDS: [&str; 8] = [
+        "golf", "alpha", "hotel", "echo", "bravo", "foxtrot", "delta", "charlie",
+    ];
+
+    #[test]
+    fn map_order() {
+        let mut map: DeterministicHashMap<&str, usize> = WORDS.iter().copied().zip(0..).collect();
+        assert_eq!(map.insert("echo", 30), Some(3));
+        assert_eq!(map.remove("alpha"), Some(1));
+        assert_eq!(map.remove("alpha"), None);
+        assert_eq!(map.remove("delta"), Some(6));
+        map.insert("alpha", 10);
+        *map.get_mut("golf").unwrap() += 100;
+
+        assert_eq!(
+            map.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+            [
+                ("golf", 100),
+                ("hotel", 2),
+                ("echo", 30),
+                ("bravo", 4),
+                ("foxtrot", 5),
+                ("charlie", 7),
+                ("alpha", 10),
+            ]
+        );
+        for (key, value) in &map {
+            assert_eq!(map.get(key), Some(value));
+        }
+        assert!(!map.contains_key("delta"));
+        assert_eq!(map.len(), 7);
+    }
+
+    #[test]
+    fn map_equality_ignores_order() {
+        let forward: DeterministicHashMap<&str, usize> = WORDS.iter().copied().zip(0..).collect();
+        let backward: DeterministicHashMap<&str, usize> =
+            WORDS.iter().copied().zip(0..8).rev().collect();
+        assert_eq!(forward, backward);
+        assert!(forward.keys().eq(WORDS.iter()));
+        assert!(backward.keys().eq(WORDS.iter().rev()));
+    }
+
+    #[test]
+    fn set_order() {
+        let mut set: DeterministicHashSet<u32> = (0..1000).rev().collect();
+        assert!(!set.insert(500));
+        for i in (0..1000).step_by(3) {
+            assert!(set.remove(&i));
+        }
+        assert!(set.insert(3));
+
+        let values: Vec<u32> = set.into_iter().collect();
+        let mut expected: Vec<u32> = (0..1000).rev().filter(|i| i % 3 != 0).collect();
+        expected.push(3);
+        assert_eq!(values, expected);
+    }
+}
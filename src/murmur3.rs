@@ -0,0 +1,295 @@
+//! The MurmurHash3 hash function, in its `x86_32` and `x64_128` variants.
+//!
+//! Both hashers are streaming versions of the reference implementation, and keep at most one
+//! block of unprocessed bytes.
+//!
+//! ```
+//! use core::hash::{Hash, Hasher};
+//! use deterministic_hash::murmur3::DeterministicMurmur3X64_128;
+//! let mut hasher = DeterministicMurmur3X64_128::default();
+//! (0x1337 as usize).hash(&mut hasher);
+//! assert_eq!(hasher.finish(), 0x070dcf77a75f4523);
+//! ```
+
+use crate::block::{read_u32, read_u64, BlockBuffer};
+use crate::{
+    BoundaryIndependent, DeterministicBuildHasher, DeterministicHasher, FixedOutputHasher,
+    HashAlgorithm,
+};
+use core::hash::{BuildHasherDefault, Hasher};
+
+/// The `MurmurHash3_x86_32` hasher.
+#[derive(Clone, Debug)]
+pub struct Murmur3X86_32 {
+    h1: u32,
+    blocks: BlockBuffer<4>,
+}
+
+/// The `MurmurHash3_x64_128` hasher.
+#[derive(Clone, Debug)]
+pub struct Murmur3X64_128 {
+    h1: u64,
+    h2: u64,
+    blocks: BlockBuffer<16>,
+}
+
+/// `DeterministicHasher` around the `MurmurHash3_x86_32` hasher.
+pub type DeterministicMurmur3X86_32 = DeterministicHasher<Murmur3X86_32>;
+
+/// `DeterministicBuildHasher` that builds `DeterministicMurmur3X86_32` hashers.
+pub type BuildDeterministicMurmur3X86_32 =
+    DeterministicBuildHasher<BuildHasherDefault<Murmur3X86_32>>;
+
+/// `DeterministicHasher` around the `MurmurHash3_x64_128` hasher.
+pub type DeterministicMurmur3X64_128 = DeterministicHasher<Murmur3X64_128>;
+
+/// `DeterministicBuildHasher` that builds `DeterministicMurmur3X64_128` hashers.
+pub type BuildDeterministicMurmur3X64_128 =
+    DeterministicBuildHasher<BuildHasherDefault<Murmur3X64_128>>;
+
+impl Murmur3X86_32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+
+    pub const fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    pub const fn with_seed(seed: u32) -> Self {
+        Self {
+            h1: seed,
+            blocks: BlockBuffer::new(),
+        }
+    }
+
+    fn mix_k1(k1: u32) -> u32 {
+        k1.wrapping_mul(Self::C1)
+            .rotate_left(15)
+            .wrapping_mul(Self::C2)
+    }
+
+    fn fmix(mut h: u32) -> u32 {
+        h ^= h >> 16;
+        h = h.wrapping_mul(0x85eb_ca6b);
+        h ^= h >> 13;
+        h = h.wrapping_mul(0xc2b2_ae35);
+        h ^ (h >> 16)
+    }
+
+    /// Returns the 32-bit hash of the bytes written so far.
+    pub fn finish32(&self) -> u32 {
+        let mut h1 = self.h1;
+        let tail = self.blocks.tail();
+        if !tail.is_empty() {
+            let mut k1 = 0u32;
+            for (i, byte) in tail.iter().enumerate() {
+                k1 |= (*byte as u32) << (8 * i);
+            }
+            h1 ^= Self::mix_k1(k1);
+        }
+        Self::fmix(h1 ^ self.blocks.len() as u32)
+    }
+}
+
+impl Murmur3X64_128 {
+    const C1: u64 = 0x87c3_7b91_1142_53d5;
+    const C2: u64 = 0x4cf5_ad43_2745_937f;
+
+    pub const fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    pub const fn with_seed(seed: u32) -> Self {
+        Self {
+            h1: seed as u64,
+            h2: seed as u64,
+            blocks: BlockBuffer::new(),
+        }
+    }
+
+    fn mix_k1(k1: u64) -> u64 {
+        k1.wrapping_mul(Self::C1)
+            .rotate_left(31)
+            .wrapping_mul(Self::C2)
+    }
+
+    fn mix_k2(k2: u64) -> u64 {
+        k2.wrapping_mul(Self::C2)
+            .rotate_left(33)
+            .wrapping_mul(Self::C1)
+    }
+
+    fn fmix(mut k: u64) -> u64 {
+        k ^= k >> 33;
+        k = k.wrapping_mul(0xff51_afd7_ed55_8ccd);
+        k ^= k >> 33;
+        k = k.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+        k ^ (k >> 33)
+    }
+
+    /// Returns the 128-bit hash of the bytes written so far.
+    ///
+    /// The first 64-bit half of the reference output forms the least significant bits, such that
+    /// `to_le_bytes` returns the reference output.
+    pub fn finish128(&self) -> u128 {
+        let (mut h1, mut h2) = (self.h1, self.h2);
+        let tail = self.blocks.tail();
+        let (mut k1, mut k2) = (0u64, 0u64);
+        for (i, byte) in tail.iter().enumerate() {
+            if i < 8 {
+                k1 |= (*byte as u64) << (8 * i);
+            } else {
+                k2 |= (*byte as u64) << (8 * (i - 8));
+            }
+        }
+        if tail.len() > 8 {
+            h2 ^= Self::mix_k2(k2);
+        }
+        if !tail.is_empty() {
+            h1 ^= Self::mix_k1(k1);
+        }
+
+        h1 ^= self.blocks.len();
+        h2 ^= self.blocks.len();
+        h1 = h1.wrapping_add(h2);
+        h2 = h2.wrapping_add(h1);
+        h1 = Self::fmix(h1);
+        h2 = Self::fmix(h2);
+        h1 = h1.wrapping_add(h2);
+        h2 = h2.wrapping_add(h1);
+        (h2 as u128) << 64 | h1 as u128
+    }
+}
+
+impl Default for Murmur3X86_32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Default for Murmur3X64_128 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the 32-bit hash zero-extended to a `u64`.
+impl Hasher for Murmur3X86_32 {
+    fn finish(&self) -> u64 {
+        self.finish32() as u64
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        let h1 = &mut self.h1;
+        self.blocks.write(bytes, |block| {
+            *h1 ^= Self::mix_k1(read_u32(block));
+            *h1 = h1.rotate_left(13).wrapping_mul(5).wrapping_add(0xe654_6b64);
+        });
+    }
+}
+
+/// Returns the first 64-bit half of the reference output.
+impl Hasher for Murmur3X64_128 {
+    fn finish(&self) -> u64 {
+        self.finish128() as u64
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        let (h1, h2) = (&mut self.h1, &mut self.h2);
+        self.blocks.write(bytes, |block| {
+            *h1 ^= Self::mix_k1(read_u64(block));
+            *h1 = h1
+                .rotate_left(27)
+                .wrapping_add(*h2)
+                .wrapping_mul(5)
+                .wrapping_add(0x52dc_e729);
+
+            *h2 ^= Self::mix_k2(read_u64(&block[8..]));
+            *h2 = h2
+                .rotate_left(31)
+                .wrapping_add(*h1)
+                .wrapping_mul(5)
+                .wrapping_add(0x3849_5ab5);
+        });
+    }
+}
+
+impl FixedOutputHasher for Murmur3X86_32 {
+    type Output = u32;
+
+    fn finish_output(&self) -> u32 {
+        self.finish32()
+    }
+}
+
+impl FixedOutputHasher for Murmur3X64_128 {
+    type Output = u128;
+
+    fn finish_output(&self) -> u128 {
+        self.finish128()
+    }
+}
+
+impl HashAlgorithm for Murmur3X86_32 {
+    fn new_hasher() -> Self {
+        Self::new()
+    }
+}
+
+impl BoundaryIndependent for Murmur3X86_32 {}
+
+impl HashAlgorithm for Murmur3X64_128 {
+    fn new_hasher() -> Self {
+        Self::new()
+    }
+}
+
+impl BoundaryIndependent for Murmur3X64_128 {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Murmur3X64_128, Murmur3X86_32};
+    use crate::testing::assert_boundary_independent;
+    use crate::FixedOutputHasher;
+    use core::hash::Hasher;
+
+    const FOX: &[u8] = b"The quick brown fox jumps over the lazy dog";
+
+    const VECTORS: [(&[u8], u32, u32, u128); 5] = [
+        (b"", 0, 0, 0),
+        (b"", 1, 0x514e28b7, 0x51622daa78f835834610abe56eff5cb5),
+        (
+            b"",
+            0xffffffff,
+            0x81f16f39,
+            0x857421121ee6446b6af1df4d9d3bc9ec,
+        ),
+        (b"abc", 0, 0xb3dd93fa, 0x3ba2744126ca2d52b4963f3f3fad7867),
+        (FOX, 0, 0x2e4ff723, 0x7a433ca9c49a9347e34bbc7bbc071b6c),
+    ];
+
+    #[test]
+    fn reference_vectors() {
+        for (input, seed, expected32, expected128) in VECTORS.iter() {
+            for chunk_size in 1..=17 {
+                let mut x86_32 = Murmur3X86_32::with_seed(*seed);
+                let mut x64_128 = Murmur3X64_128::with_seed(*seed);
+                for chunk in input.chunks(chunk_size) {
+                    x86_32.write(chunk);
+                    x64_128.write(chunk);
+                }
+                assert_eq!(x86_32.finish32(), *expected32);
+                assert_eq!(x64_128.finish128(), *expected128);
+                assert_eq!(x64_128.finish(), *expected128 as u64);
+                assert_eq!(x86_32.finish_output(), *expected32);
+                assert_eq!(x64_128.finish_output(), *expected128);
+            }
+        }
+    }
+
+    #[test]
+    fn boundary_independent() {
+        assert_boundary_independent::<Murmur3X86_32>();
+        assert_boundary_independent::<Murmur3X64_128>();
+    }
+}
@@ -0,0 +1,1007 @@
+//! Recording of the calls to a hasher, to find out why two hashes differ.
+//!
+//! A `RecordingHasher` wraps any hasher, typically a `DeterministicHasher`, and passes every call to
+//! it as an `Event` to a `Sink` before forwarding the call. `FixedTranscript` records the events in
+//! a buffer of `N` bytes for `no_std` targets, and `Transcript` records them in a `Vec` when the
+//! `alloc` feature is enabled. Both use the same compact binary format, so a transcript captured on
+//! a device can be decoded with `decode`, printed, and replayed into any other hasher with `replay`.
+//!
+//! ```
+//! use core::hash::{Hash, Hasher};
+//! use deterministic_hash::recording::{replay, FixedTranscript, RecordingHasher};
+//! use deterministic_hash::DeterministicHasher;
+//! use std::collections::hash_map::DefaultHasher;
+//!
+//! let hasher = DeterministicHasher::new(DefaultHasher::new());
+//! let mut hasher = RecordingHasher::new(hasher, FixedTranscript::<64>::new());
+//! (0x1337 as usize, -1i8).hash(&mut hasher);
+//! let (hasher, transcript) = hasher.into_parts();
+//! assert_eq!(transcript.to_string(), "write_usize(4919)\nwrite_i8(-1)\n");
+//!
+//! let mut replayed = DeterministicHasher::new(DefaultHasher::new());
+//! replay(transcript.as_bytes(), &mut replayed).unwrap();
+//! assert_eq!(replayed.finish(), hasher.finish());
+//! ```
+//!
+//! # Format
+//!
+//! A transcript starts with the magic bytes `DHT` and the format version `0x01`, followed by the
+//! events. Every event starts with a tag:
+//! * `0x01` to `0x05`: `write_u8` to `write_u128`, followed by the value in little-endian order.
+//! * `0x06`: `write_usize`, followed by the value in LEB128.
+//! * `0x11` to `0x15`: `write_i8` to `write_i128`, followed by the value in little-endian order.
+//! * `0x16`: `write_isize`, followed by the zigzag encoded value in LEB128.
+//! * `0x20`: `write`, followed by the length in LEB128 and the bytes.
+//! * `0x21`: `write_length_prefix`, followed by the length in LEB128.
+//! * `0x22`: `write_str`, followed by the length in LEB128 and the UTF-8 bytes.
+
+use crate::FixedOutputHasher;
+use core::convert::TryFrom;
+use core::fmt;
+use core::hash::Hasher;
+
+#[cfg(feature = "alloc")]
+use crate::DeterministicHasher;
+#[cfg(feature = "alloc")]
+use alloc::string::{String, ToString};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::hash::Hash;
+
+/// The bytes at the start of every transcript: the magic `DHT` followed by the format version.
+pub const HEADER: [u8; 4] = *b"DHT\x01";
+
+const TAG_U8: u8 = 0x01;
+const TAG_U16: u8 = 0x02;
+const TAG_U32: u8 = 0x03;
+const TAG_U64: u8 = 0x04;
+const TAG_U128: u8 = 0x05;
+const TAG_USIZE: u8 = 0x06;
+const TAG_I8: u8 = 0x11;
+const TAG_I16: u8 = 0x12;
+const TAG_I32: u8 = 0x13;
+const TAG_I64: u8 = 0x14;
+const TAG_I128: u8 = 0x15;
+const TAG_ISIZE: u8 = 0x16;
+const TAG_WRITE: u8 = 0x20;
+const TAG_LENGTH_PREFIX: u8 = 0x21;
+const TAG_STR: u8 = 0x22;
+
+/// A call to a hasher.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event<'a> {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    Usize(usize),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    Isize(isize),
+    Write(&'a [u8]),
+    /// Only recorded with the `nightly` feature, without it a length prefix is a `Usize`.
+    LengthPrefix(usize),
+    /// Only recorded with the `nightly` feature, without it a string is a `Write` and a `U8`.
+    Str(&'a str),
+}
+
+/// The tag and fixed-size part of an encoded event.
+struct Head {
+    bytes: [u8; 17],
+    len: usize,
+}
+
+impl Head {
+    fn new(tag: u8) -> Self {
+        let mut bytes = [0; 17];
+        bytes[0] = tag;
+        Self { bytes, len: 1 }
+    }
+
+    fn extend(mut self, bytes: &[u8]) -> Self {
+        self.bytes[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+        self
+    }
+
+    fn leb128(mut self, mut value: u64) -> Self {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                return self.extend(&[byte]);
+            }
+            self = self.extend(&[byte | 0x80]);
+        }
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+fn zigzag(i: i64) -> u64 {
+    ((i << 1) ^ (i >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+impl<'a> Event<'a> {
+    fn encode_parts(&self) -> (Head, &'a [u8]) {
+        match *self {
+            Event::U8(i) => (Head::new(TAG_U8).extend(&i.to_le_bytes()), &[]),
+            Event::U16(i) => (Head::new(TAG_U16).extend(&i.to_le_bytes()), &[]),
+            Event::U32(i) => (Head::new(TAG_U32).extend(&i.to_le_bytes()), &[]),
+            Event::U64(i) => (Head::new(TAG_U64).extend(&i.to_le_bytes()), &[]),
+            Event::U128(i) => (Head::new(TAG_U128).extend(&i.to_le_bytes()), &[]),
+            Event::Usize(i) => (Head::new(TAG_USIZE).leb128(i as u64), &[]),
+            Event::I8(i) => (Head::new(TAG_I8).extend(&i.to_le_bytes()), &[]),
+            Event::I16(i) => (Head::new(TAG_I16).extend(&i.to_le_bytes()), &[]),
+            Event::I32(i) => (Head::new(TAG_I32).extend(&i.to_le_bytes()), &[]),
+            Event::I64(i) => (Head::new(TAG_I64).extend(&i.to_le_bytes()), &[]),
+            Event::I128(i) => (Head::new(TAG_I128).extend(&i.to_le_bytes()), &[]),
+            Event::Isize(i) => (Head::new(TAG_ISIZE).leb128(zigzag(i as i64)), &[]),
+            Event::Write(bytes) => (Head::new(TAG_WRITE).leb128(bytes.len() as u64), bytes),
+            Event::LengthPrefix(len) => (Head::new(TAG_LENGTH_PREFIX).leb128(len as u64), &[]),
+            Event::Str(s) => (Head::new(TAG_STR).leb128(s.len() as u64), s.as_bytes()),
+        }
+    }
+
+    /// The number of bytes of the encoded event.
+    pub fn encoded_len(&self) -> usize {
+        let (head, tail) = self.encode_parts();
+        head.len + tail.len()
+    }
+
+    /// Passes the encoded event to `emit`, in one or two parts.
+    pub fn encode(&self, mut emit: impl FnMut(&[u8])) {
+        let (head, tail) = self.encode_parts();
+        emit(head.as_bytes());
+        if !tail.is_empty() {
+            emit(tail);
+        }
+    }
+
+    /// Makes the recorded call on the hasher.
+    ///
+    /// Without the `nightly` feature, length prefixes and strings are written like the default
+    /// implementations of `Hasher::write_length_prefix` and `Hasher::write_str` in `core`.
+    pub fn replay<H: Hasher>(&self, state: &mut H) {
+        match *self {
+            Event::U8(i) => state.write_u8(i),
+            Event::U16(i) => state.write_u16(i),
+            Event::U32(i) => state.write_u32(i),
+            Event::U64(i) => state.write_u64(i),
+            Event::U128(i) => state.write_u128(i),
+            Event::Usize(i) => state.write_usize(i),
+            Event::I8(i) => state.write_i8(i),
+            Event::I16(i) => state.write_i16(i),
+            Event::I32(i) => state.write_i32(i),
+            Event::I64(i) => state.write_i64(i),
+            Event::I128(i) => state.write_i128(i),
+            Event::Isize(i) => state.write_isize(i),
+            Event::Write(bytes) => state.write(bytes),
+            #[cfg(feature = "nightly")]
+            Event::LengthPrefix(len) => state.write_length_prefix(len),
+            #[cfg(not(feature = "nightly"))]
+            Event::LengthPrefix(len) => state.write_usize(len),
+            #[cfg(feature = "nightly")]
+            Event::Str(s) => state.write_str(s),
+            #[cfg(not(feature = "nightly"))]
+            Event::Str(s) => {
+                state.write(s.as_bytes());
+                state.write_u8(0xff);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Event<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::U8(i) => write!(f, "write_u8({})", i),
+            Event::U16(i) => write!(f, "write_u16({})", i),
+            Event::U32(i) => write!(f, "write_u32({})", i),
+            Event::U64(i) => write!(f, "write_u64({})", i),
+            Event::U128(i) => write!(f, "write_u128({})", i),
+            Event::Usize(i) => write!(f, "write_usize({})", i),
+            Event::I8(i) => write!(f, "write_i8({})", i),
+            Event::I16(i) => write!(f, "write_i16({})", i),
+            Event::I32(i) => write!(f, "write_i32({})", i),
+            Event::I64(i) => write!(f, "write_i64({})", i),
+            Event::I128(i) => write!(f, "write_i128({})", i),
+            Event::Isize(i) => write!(f, "write_isize({})", i),
+            Event::Write(bytes) => write!(f, "write({}, {:02x?})", bytes.len(), bytes),
+            Event::LengthPrefix(len) => write!(f, "write_length_prefix({})", len),
+            Event::Str(s) => write!(f, "write_str({:?})", s),
+        }
+    }
+}
+
+/// A transcript could not be decoded.
+///
+/// Offsets are in bytes from the start of the transcript.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The transcript does not start with the magic bytes `DHT`.
+    Magic,
+    /// The transcript has a format version that this version of the crate cannot decode.
+    Version(u8),
+    /// The transcript ends in the middle of an event.
+    UnexpectedEnd {
+        offset: usize,
+    },
+    UnknownTag {
+        offset: usize,
+        tag: u8,
+    },
+    /// A length or `usize` does not fit a `usize` of this target.
+    Overflow {
+        offset: usize,
+    },
+    InvalidUtf8 {
+        offset: usize,
+    },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Magic => write!(f, "not a transcript"),
+            DecodeError::Version(version) => {
+                write!(f, "unsupported transcript version {}", version)
+            }
+            DecodeError::UnexpectedEnd { offset } => {
+                write!(f, "transcript ends in an event at offset {}", offset)
+            }
+            DecodeError::UnknownTag { offset, tag } => {
+                write!(f, "unknown tag {:#04x} at offset {}", tag, offset)
+            }
+            DecodeError::Overflow { offset } => {
+                write!(f, "value at offset {} does not fit a usize", offset)
+            }
+            DecodeError::InvalidUtf8 { offset } => {
+                write!(f, "string at offset {} is not UTF-8", offset)
+            }
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+/// Iterator over the events of a transcript, which stops after the first error.
+///
+/// Displays as one event per line.
+#[derive(Clone, Debug)]
+pub struct Events<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+/// Decodes the events of a transcript, after checking its header.
+pub fn decode(transcript: &[u8]) -> Result<Events<'_>, DecodeError> {
+    if transcript.len() < HEADER.len() || transcript[..3] != HEADER[..3] {
+        return Err(DecodeError::Magic);
+    }
+    if transcript[3] != HEADER[3] {
+        return Err(DecodeError::Version(transcript[3]));
+    }
+    Ok(Events {
+        bytes: transcript,
+        offset: HEADER.len(),
+    })
+}
+
+/// Replays the events of a transcript on the hasher.
+///
+/// The events before an error in the transcript are replayed.
+pub fn replay<H: Hasher>(transcript: &[u8], state: &mut H) -> Result<(), DecodeError> {
+    for event in decode(transcript)? {
+        event?.replay(state);
+    }
+    Ok(())
+}
+
+impl<'a> Events<'a> {
+    /// The offset of the next event from the start of the transcript.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if self.bytes.len() - self.offset < len {
+            return Err(DecodeError::UnexpectedEnd {
+                offset: self.offset,
+            });
+        }
+        let bytes = &self.bytes[self.offset..self.offset + len];
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn take_leb128(&mut self) -> Result<u64, DecodeError> {
+        let offset = self.offset;
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let [byte] = self.take_array()?;
+            let bits = (byte & 0x7f) as u64;
+            if bits << shift >> shift != bits {
+                return Err(DecodeError::Overflow { offset });
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(DecodeError::Overflow { offset })
+    }
+
+    fn take_usize(&mut self) -> Result<usize, DecodeError> {
+        let offset = self.offset;
+        let value = self.take_leb128()?;
+        usize::try_from(value).map_err(|_| DecodeError::Overflow { offset })
+    }
+
+    fn take_isize(&mut self) -> Result<isize, DecodeError> {
+        let offset = self.offset;
+        let value = unzigzag(self.take_leb128()?);
+        isize::try_from(value).map_err(|_| DecodeError::Overflow { offset })
+    }
+
+    fn next_event(&mut self) -> Result<Event<'a>, DecodeError> {
+        let offset = self.offset;
+        let [tag] = self.take_array()?;
+        Ok(match tag {
+            TAG_U8 => Event::U8(u8::from_le_bytes(self.take_array()?)),
+            TAG_U16 => Event::U16(u16::from_le_bytes(self.take_array()?)),
+            TAG_U32 => Event::U32(u32::from_le_bytes(self.take_array()?)),
+            TAG_U64 => Event::U64(u64::from_le_bytes(self.take_array()?)),
+            TAG_U128 => Event::U128(u128::from_le_bytes(self.take_array()?)),
+            TAG_USIZE => Event::Usize(self.take_usize()?),
+            TAG_I8 => Event::I8(i8::from_le_bytes(self.take_array()?)),
+            TAG_I16 => Event::I16(i16::from_le_bytes(self.take_array()?)),
+            TAG_I32 => Event::I32(i32::from_le_bytes(self.take_array()?)),
+            TAG_I64 => Event::I64(i64::from_le_bytes(self.take_array()?)),
+            TAG_I128 => Event::I128(i128::from_le_bytes(self.take_array()?)),
+            TAG_ISIZE => Event::Isize(self.take_isize()?),
+            TAG_WRITE => {
+                let len = self.take_usize()?;
+                Event::Write(self.take(len)?)
+            }
+            TAG_LENGTH_PREFIX => Event::LengthPrefix(self.take_usize()?),
+            TAG_STR => {
+                let len = self.take_usize()?;
+                let offset = self.offset;
+                let bytes = self.take(len)?;
+                let s =
+                    core::str::from_utf8(bytes).map_err(|_| DecodeError::InvalidUtf8 { offset })?;
+                Event::Str(s)
+            }
+            tag => return Err(DecodeError::UnknownTag { offset, tag }),
+        })
+    }
+}
+
+impl<'a> Iterator for Events<'a> {
+    type Item = Result<Event<'a>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset == self.bytes.len() {
+            return None;
+        }
+        let event = self.next_event();
+        if event.is_err() {
+            self.offset = self.bytes.len();
+        }
+        Some(event)
+    }
+}
+
+impl fmt::Display for Events<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for event in self.clone() {
+            match event {
+                Ok(event) => writeln!(f, "{}", event)?,
+                Err(error) => writeln!(f, "error: {}", error)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A destination for the events of a `RecordingHasher`.
+pub trait Sink {
+    fn record(&mut self, event: Event<'_>);
+}
+
+impl<S: Sink + ?Sized> Sink for &mut S {
+    fn record(&mut self, event: Event<'_>) {
+        (**self).record(event)
+    }
+}
+
+/// A transcript in a buffer of `N` bytes.
+///
+/// When an event does not fit, it and all events after it are dropped and the transcript is marked
+/// as truncated, such that the recorded events remain a prefix of all events.
+#[derive(Clone, Debug)]
+pub struct FixedTranscript<const N: usize> {
+    buffer: [u8; N],
+    len: usize,
+    truncated: bool,
+}
+
+impl<const N: usize> FixedTranscript<N> {
+    pub const fn new() -> Self {
+        let mut buffer = [0; N];
+        if N < HEADER.len() {
+            return Self {
+                buffer,
+                len: 0,
+                truncated: true,
+            };
+        }
+        let mut i = 0;
+        while i < HEADER.len() {
+            buffer[i] = HEADER[i];
+            i += 1;
+        }
+        Self {
+            buffer,
+            len: HEADER.len(),
+            truncated: false,
+        }
+    }
+
+    /// The encoded transcript, including its header.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buffer[..self.len]
+    }
+
+    /// Returns whether events were dropped because the buffer was full.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    pub fn events(&self) -> Events<'_> {
+        Events {
+            bytes: self.as_bytes(),
+            offset: core::cmp::min(self.len, HEADER.len()),
+        }
+    }
+}
+
+impl<const N: usize> Default for FixedTranscript<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Sink for FixedTranscript<N> {
+    fn record(&mut self, event: Event<'_>) {
+        if self.truncated || N - self.len < event.encoded_len() {
+            self.truncated = true;
+            return;
+        }
+        let (buffer, len) = (&mut self.buffer, &mut self.len);
+        event.encode(|bytes| {
+            buffer[*len..*len + bytes.len()].copy_from_slice(bytes);
+            *len += bytes.len();
+        });
+    }
+}
+
+/// Displays one event per line, followed by a line for the truncation.
+impl<const N: usize> fmt::Display for FixedTranscript<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.events().fmt(f)?;
+        if self.truncated {
+            writeln!(f, "truncated")?;
+        }
+        Ok(())
+    }
+}
+
+/// A transcript in a `Vec`.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transcript(Vec<u8>);
+
+#[cfg(feature = "alloc")]
+impl Transcript {
+    pub fn new() -> Self {
+        Self(HEADER.to_vec())
+    }
+
+    /// The encoded transcript, including its header.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+
+    pub fn events(&self) -> Events<'_> {
+        Events {
+            bytes: &self.0,
+            offset: HEADER.len(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Default for Transcript {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Sink for Transcript {
+    fn record(&mut self, event: Event<'_>) {
+        let bytes = &mut self.0;
+        event.encode(|part| bytes.extend_from_slice(part));
+    }
+}
+
+/// Displays one event per line.
+#[cfg(feature = "alloc")]
+impl fmt::Display for Transcript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.events().fmt(f)
+    }
+}
+
+/// Wrapper around any hasher that records every call to it in a `Sink`.
+#[derive(Clone, Debug)]
+pub struct RecordingHasher<T: Hasher, S: Sink> {
+    inner: T,
+    sink: S,
+}
+
+impl<T: Hasher, S: Sink> RecordingHasher<T, S> {
+    pub fn new(inner: T, sink: S) -> Self {
+        Self { inner, sink }
+    }
+
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    pub fn as_inner(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn into_parts(self) -> (T, S) {
+        (self.inner, self.sink)
+    }
+}
+
+impl<T: Hasher, S: Sink> Hasher for RecordingHasher<T, S> {
+    fn finish(&self) -> u64 {
+        self.inner.finish()
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.sink.record(Event::Write(bytes));
+        self.inner.write(bytes);
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.sink.record(Event::U8(i));
+        self.inner.write_u8(i)
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.sink.record(Event::U16(i));
+        self.inner.write_u16(i)
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.sink.record(Event::U32(i));
+        self.inner.write_u32(i)
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.sink.record(Event::U64(i));
+        self.inner.write_u64(i)
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.sink.record(Event::U128(i));
+        self.inner.write_u128(i)
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.sink.record(Event::Usize(i));
+        self.inner.write_usize(i)
+    }
+
+    fn write_i8(&mut self, i: i8) {
+        self.sink.record(Event::I8(i));
+        self.inner.write_i8(i)
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.sink.record(Event::I16(i));
+        self.inner.write_i16(i)
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.sink.record(Event::I32(i));
+        self.inner.write_i32(i)
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.sink.record(Event::I64(i));
+        self.inner.write_i64(i)
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.sink.record(Event::I128(i));
+        self.inner.write_i128(i)
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.sink.record(Event::Isize(i));
+        self.inner.write_isize(i)
+    }
+
+    #[cfg(feature = "nightly")]
+    fn write_length_prefix(&mut self, len: usize) {
+        self.sink.record(Event::LengthPrefix(len));
+        self.inner.write_length_prefix(len)
+    }
+
+    #[cfg(feature = "nightly")]
+    fn write_str(&mut self, s: &str) {
+        self.sink.record(Event::Str(s));
+        self.inner.write_str(s)
+    }
+}
+
+impl<T: FixedOutputHasher, S: Sink> FixedOutputHasher for RecordingHasher<T, S> {
+    type Output = T::Output;
+
+    fn finish_output(&self) -> T::Output {
+        self.inner.finish_output()
+    }
+}
+
+/// Collects the bytes that a `DeterministicHasher` passes on, to compare them directly.
+#[cfg(feature = "alloc")]
+#[derive(Default)]
+struct ByteStream(Vec<u8>);
+
+#[cfg(feature = "alloc")]
+impl Hasher for ByteStream {
+    /// Unused, as only the written bytes are compared.
+    fn finish(&self) -> u64 {
+        0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+}
+
+/// The first call that differs between hashing two values, see `assert_hash_eq!`.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// The number of calls before the first call that differs.
+    pub index: usize,
+    /// The number of bytes that the `DeterministicHasher` passed on before the first call that
+    /// differs.
+    pub offset: usize,
+    /// The call for the left value, or `None` when it made fewer calls.
+    pub left: Option<String>,
+    /// The call for the right value, or `None` when it made fewer calls.
+    pub right: Option<String>,
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let end = "end";
+        write!(
+            f,
+            "event {} at byte {}: {} vs {}",
+            self.index,
+            self.offset,
+            self.left.as_deref().unwrap_or(end),
+            self.right.as_deref().unwrap_or(end)
+        )
+    }
+}
+
+/// Hashes both values through a `DeterministicHasher`, and returns the first call that differs
+/// when the bytes that it passes on to the inner hasher differ.
+///
+/// Calls may differ while the bytes are the same, such as `write_u16(0x0201)` and
+/// `write_u8(1); write_u8(2)`, in which case the values hash the same and `None` is returned.
+#[cfg(feature = "alloc")]
+pub fn first_divergence<L: Hash + ?Sized, R: Hash + ?Sized>(
+    left: &L,
+    right: &R,
+) -> Option<Divergence> {
+    fn record<T: Hash + ?Sized>(value: &T) -> (Vec<u8>, Transcript) {
+        let hasher = DeterministicHasher::new(ByteStream::default());
+        let mut hasher = RecordingHasher::new(hasher, Transcript::new());
+        value.hash(&mut hasher);
+        let (hasher, transcript) = hasher.into_parts();
+        (hasher.into_inner().0, transcript)
+    }
+
+    let (left_bytes, left) = record(left);
+    let (right_bytes, right) = record(right);
+    if left_bytes == right_bytes {
+        return None;
+    }
+
+    let mut lefts = left.events().filter_map(Result::ok);
+    let mut rights = right.events().filter_map(Result::ok);
+    let mut common = DeterministicHasher::new(ByteStream::default());
+    let mut index = 0;
+    loop {
+        match (lefts.next(), rights.next()) {
+            (Some(left), Some(right)) if left == right => left.replay(&mut common),
+            (left, right) => {
+                return Some(Divergence {
+                    index,
+                    offset: common.as_inner().0.len(),
+                    left: left.as_ref().map(ToString::to_string),
+                    right: right.as_ref().map(ToString::to_string),
+                })
+            }
+        }
+        index += 1;
+    }
+}
+
+/// Asserts that two values hash the same through a `DeterministicHasher`, for any inner hasher.
+///
+/// On failure, the panic message shows the first call to the hasher that differs, such as
+/// `event 17 at byte 120: write_usize(3) vs write_usize(4)`.
+///
+/// ```
+/// use deterministic_hash::assert_hash_eq;
+/// assert_hash_eq!(0x0201u16, (1u8, 2u8));
+/// ```
+///
+/// ```should_panic
+/// use deterministic_hash::assert_hash_eq;
+/// assert_hash_eq!((1u8, 3u8), (1u8, 4u8));
+/// ```
+#[cfg(feature = "alloc")]
+#[macro_export]
+macro_rules! assert_hash_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        if let Some(divergence) = $crate::recording::first_divergence(&$left, &$right) {
+            panic!(
+                "assertion `hash(left) == hash(right)` failed\n{}",
+                divergence
+            );
+        }
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        if let Some(divergence) = $crate::recording::first_divergence(&$left, &$right) {
+            panic!(
+                "assertion `hash(left) == hash(right)` failed: {}\n{}",
+                format_args!($($arg)+),
+                divergence
+            );
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{decode, replay, DecodeError, Event, FixedTranscript, RecordingHasher, Sink};
+    use crate::DeterministicHasher;
+    use core::hash::{Hash, Hasher};
+    use std::collections::hash_map::DefaultHasher;
+    use std::string::ToString;
+    use std::vec::Vec;
+
+    const EVENTS: [Event<'static>; 15] = [
+        Event::U8(1),
+        Event::U16(0x0203),
+        Event::U32(4),
+        Event::U64(5),
+        Event::U128(6),
+        Event::Usize(300),
+        Event::I8(-1),
+        Event::I16(-2),
+        Event::I32(-3),
+        Event::I64(-4),
+        Event::I128(-5),
+        Event::Isize(-300),
+        Event::Write(b"ab"),
+        Event::LengthPrefix(2),
+        Event::Str("\u{e9}"),
+    ];
+
+    #[test]
+    fn format() {
+        let mut transcript = FixedTranscript::<128>::new();
+        for event in EVENTS.iter() {
+            transcript.record(*event);
+        }
+        assert!(!transcript.is_truncated());
+
+        let mut expected = Vec::new();
+        expected.extend_from_slice(b"DHT\x01");
+        expected.extend_from_slice(&[0x01, 1, 0x02, 3, 2]);
+        expected.extend_from_slice(&[0x03, 4, 0, 0, 0]);
+        expected.extend_from_slice(&[0x04, 5, 0, 0, 0, 0, 0, 0, 0]);
+        expected.extend_from_slice(&[0x05, 6, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        expected.extend_from_slice(&[0x06, 0xac, 0x02]);
+        expected.extend_from_slice(&[0x11, 0xff, 0x12, 0xfe, 0xff]);
+        expected.extend_from_slice(&[0x13, 0xfd, 0xff, 0xff, 0xff]);
+        expected.extend_from_slice(&[0x14, 0xfc, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+        expected.extend_from_slice(&[0x15, 0xfb]);
+        expected.extend_from_slice(&[0xff; 15]);
+        expected.extend_from_slice(&[0x16, 0xd7, 0x04]);
+        expected.extend_from_slice(&[0x20, 2, b'a', b'b', 0x21, 2, 0x22, 2, 0xc3, 0xa9]);
+        assert_eq!(transcript.as_bytes(), &expected[..]);
+
+        let decoded: Result<Vec<_>, _> = decode(transcript.as_bytes()).unwrap().collect();
+        assert_eq!(decoded.unwrap(), EVENTS);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn first_divergence() {
+        use super::{first_divergence, Divergence};
+
+        #[derive(Hash)]
+        struct Packet<'a> {
+            kind: u8,
+            payload: &'a [u16],
+        }
+
+        let left = Packet {
+            kind: 1,
+            payload: &[1, 2, 3],
+        };
+        let right = Packet {
+            kind: 1,
+            payload: &[1, 2, 3, 4],
+        };
+        assert_eq!(first_divergence(&left, &left), None);
+        assert_eq!(first_divergence(&0x0201u16, &(1u8, 2u8)), None);
+
+        let divergence = first_divergence(&left, &right).unwrap();
+        #[cfg(not(feature = "nightly"))]
+        let expected = ("write_usize(3)", "write_usize(4)");
+        #[cfg(feature = "nightly")]
+        let expected = ("write_length_prefix(3)", "write_length_prefix(4)");
+        assert_eq!(
+            divergence,
+            Divergence {
+                index: 1,
+                offset: 1,
+                left: Some(expected.0.to_string()),
+                right: Some(expected.1.to_string()),
+            }
+        );
+
+        let divergence = first_divergence(&(1u8, 2u8), &1u8).unwrap();
+        assert_eq!(
+            divergence.to_string(),
+            "event 1 at byte 1: write_u8(2) vs end"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    #[should_panic(expected = "failed: packets\nevent 0 at byte 0: write_u32(1) vs write_u32(2)")]
+    fn assert_hash_eq() {
+        assert_hash_eq!(1u32, 1u32);
+        assert_hash_eq!(1u32, 2u32, "packets");
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn vec_transcript() {
+        let mut fixed = FixedTranscript::<128>::new();
+        let mut transcript = super::Transcript::new();
+        for event in EVENTS.iter() {
+            fixed.record(*event);
+            transcript.record(*event);
+        }
+        assert_eq!(transcript.as_bytes(), fixed.as_bytes());
+        assert_eq!(transcript.to_string(), fixed.to_string());
+    }
+
+    #[test]
+    fn records_and_replays() {
+        let value = (1u8, -2i16, 0x1337usize, -3isize, [4u32, 5], 6u128);
+        let mut hasher = RecordingHasher::new(
+            DeterministicHasher::new(DefaultHasher::new()),
+            FixedTranscript::<128>::new(),
+        );
+        value.hash(&mut hasher);
+        hasher.write(b"abc");
+
+        let mut expected = DeterministicHasher::new(DefaultHasher::new());
+        value.hash(&mut expected);
+        expected.write(b"abc");
+        assert_eq!(hasher.finish(), expected.finish());
+
+        let mut replayed = DeterministicHasher::new(DefaultHasher::new());
+        replay(hasher.sink().as_bytes(), &mut replayed).unwrap();
+        assert_eq!(replayed.finish(), expected.finish());
+    }
+
+    #[test]
+    fn truncates() {
+        let mut transcript = FixedTranscript::<8>::new();
+        transcript.record(Event::U8(1));
+        transcript.record(Event::U32(2));
+        transcript.record(Event::U8(3));
+        assert!(transcript.is_truncated());
+        assert_eq!(transcript.as_bytes(), b"DHT\x01\x01\x01");
+        assert_eq!(transcript.to_string(), "write_u8(1)\ntruncated\n");
+
+        let mut transcript = FixedTranscript::<2>::new();
+        transcript.record(Event::U8(1));
+        assert!(transcript.is_truncated());
+        assert_eq!(transcript.events().count(), 0);
+    }
+
+    #[test]
+    fn decode_errors() {
+        let errors: [(&[u8], DecodeError); 7] = [
+            (b"DH", DecodeError::Magic),
+            (b"XHT\x01", DecodeError::Magic),
+            (b"DHT\x02", DecodeError::Version(2)),
+            (
+                b"DHT\x01\x01\x01\x03\x01",
+                DecodeError::UnexpectedEnd { offset: 7 },
+            ),
+            (
+                b"DHT\x01\x07",
+                DecodeError::UnknownTag { offset: 4, tag: 7 },
+            ),
+            (
+                b"DHT\x01\x06\xff\xff\xff\xff\xff\xff\xff\xff\xff\x7f",
+                DecodeError::Overflow { offset: 5 },
+            ),
+            (
+                b"DHT\x01\x22\x01\xff",
+                DecodeError::InvalidUtf8 { offset: 6 },
+            ),
+        ];
+        for (transcript, expected) in errors.iter() {
+            let error = match decode(transcript) {
+                Ok(mut events) => events.find_map(Result::err),
+                Err(error) => Some(error),
+            };
+            assert_eq!(error, Some(*expected));
+        }
+
+        let events = decode(b"DHT\x01\x01\x01\x07").unwrap();
+        assert_eq!(
+            events.to_string(),
+            "write_u8(1)\nerror: unknown tag 0x07 at offset 6\n"
+        );
+    }
+}
@@ -0,0 +1,383 @@
+//! A check at runtime that this compiler and target hash to the canonical byte stream.
+//!
+//! `self_check::<A>()` hashes a built-in corpus of values through a `DeterministicHasher`. The
+//! corpus covers every `write_*` method, negative `isize`, slices, strings, enums, `Option` and
+//! tuples. The check compares the bytes written for every value with its compiled-in canonical
+//! stream, and the output of the algorithm `A` for the whole corpus with its compiled-in output.
+//!
+//! It has no dependencies on `std` or `alloc`, so firmware can run it at boot, and integration
+//! tests can run it on every target:
+//!
+//! ```
+//! # #[cfg(feature = "fnv")]
+//! deterministic_hash::self_check::<deterministic_hash::fnv::Fnv1a64>().unwrap();
+//! ```
+//!
+//! All built-in algorithms except `digest::DigestHasher` implement `SelfCheckAlgorithm`.
+
+use crate::{
+    BoundaryIndependent, DeterministicHasher, FixedOutputHasher, HashAlgorithm, StableHash,
+};
+use core::fmt;
+use core::hash::{Hash, Hasher};
+
+/// A hash algorithm with a compiled-in output for the corpus of `self_check`.
+pub trait SelfCheckAlgorithm: HashAlgorithm + BoundaryIndependent {
+    /// The output for the canonical streams of all values of the corpus, in order.
+    const CORPUS_OUTPUT: Self::Output;
+}
+
+/// The reason why `self_check` failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mismatch {
+    /// The value of a case of the corpus was hashed to other bytes than its canonical stream,
+    /// starting at byte `offset` of the stream.
+    Stream { case: &'static str, offset: usize },
+    /// The canonical streams were written, but the algorithm returned another output.
+    Output { algorithm: &'static str },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::Stream { case, offset } => write!(
+                f,
+                "case `{}` differs from its canonical stream at byte {}",
+                case, offset
+            ),
+            Mismatch::Output { algorithm } => {
+                write!(f, "{} returned another output for the corpus", algorithm)
+            }
+        }
+    }
+}
+
+impl core::error::Error for Mismatch {}
+
+/// A value of the corpus, with the bytes a `DeterministicHasher` writes for it.
+struct Case {
+    name: &'static str,
+    hash: fn(&mut dyn Hasher),
+    stream: &'static [u8],
+}
+
+#[derive(Hash)]
+enum Shape {
+    Point,
+    Circle(u32),
+    Rect { width: u16, height: u16 },
+}
+
+fn unsigned(mut state: &mut dyn Hasher) {
+    (0xa5u8, 0x1234u16, 0xdead_beefu32, 0x0123_4567_89ab_cdefu64).hash(&mut state);
+    ((1u128 << 100) | 7, 0x1337usize).hash(&mut state);
+}
+
+fn signed(mut state: &mut dyn Hasher) {
+    (-1i8, -300i16, -70_000i32, i64::MIN, -2i128).hash(&mut state);
+}
+
+fn isize(mut state: &mut dyn Hasher) {
+    (-5isize, i16::MIN as isize, 42isize).hash(&mut state);
+}
+
+fn bytes(state: &mut dyn Hasher) {
+    state.write(b"\x00\x01\xfe\xff");
+    state.write(b"");
+}
+
+fn slices(mut state: &mut dyn Hasher) {
+    [1u8, 2, 3][..].hash(&mut state);
+    [[4u8, 5], [6, 7]].hash(&mut state);
+    // `core` writes slices of integers wider than `u8` as their native memory, so those are only
+    // canonical with `StableHash`.
+    [-1i32, 2][..].stable_hash(&mut state);
+}
+
+/// `core` terminates strings with `0xFF`, unless `DeterministicHasher` implements `write_str`.
+fn strings(mut state: &mut dyn Hasher) {
+    #[cfg(feature = "nightly")]
+    ("", "déjà vu").hash(&mut state);
+    #[cfg(not(feature = "nightly"))]
+    ("", "déjà vu").stable_hash(&mut state);
+}
+
+fn enums(mut state: &mut dyn Hasher) {
+    let shapes = [
+        Shape::Point,
+        Shape::Circle(3),
+        Shape::Rect {
+            width: 4,
+            height: 5,
+        },
+    ];
+    shapes.hash(&mut state);
+}
+
+fn options(mut state: &mut dyn Hasher) {
+    (Some(7u32), None::<u16>, Some(Some(-1i8))).hash(&mut state);
+}
+
+fn tuples(mut state: &mut dyn Hasher) {
+    ((1u8, (2u16,), ()), true, 'x', (-3i64, false)).hash(&mut state);
+}
+
+const CORPUS: &[Case] = &[
+    Case {
+        name: "unsigned",
+        hash: unsigned,
+        stream: &[
+            0xa5, 0x34, 0x12, 0xef, 0xbe, 0xad, 0xde, 0xef, 0xcd, 0xab, 0x89, 0x67, 0x45, 0x23,
+            0x01, 0x07, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10,
+            0x00, 0x00, 0x00, 0x37, 0x13, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ],
+    },
+    Case {
+        name: "signed",
+        hash: signed,
+        stream: &[
+            0xff, 0xd4, 0xfe, 0x90, 0xee, 0xfe, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x80, 0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff,
+        ],
+    },
+    Case {
+        name: "isize",
+        hash: isize,
+        stream: &[
+            0xfb, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x80, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0x2a, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ],
+    },
+    Case {
+        name: "bytes",
+        hash: bytes,
+        stream: &[0x00, 0x01, 0xfe, 0xff],
+    },
+    Case {
+        name: "slices",
+        hash: slices,
+        stream: &[
+            0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x02, 0x03, 0x02, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04,
+            0x05, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x06, 0x07, 0x02, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0x02, 0x00, 0x00, 0x00,
+        ],
+    },
+    Case {
+        name: "strings",
+        hash: strings,
+        stream: &[
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x09, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x64, 0xc3, 0xa9, 0x6a, 0xc3, 0xa0, 0x20, 0x76, 0x75,
+        ],
+    },
+    Case {
+        name: "enums",
+        hash: enums,
+        stream: &[
+            0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00,
+            0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x00, 0x05, 0x00,
+        ],
+    },
+    Case {
+        name: "options",
+        hash: options,
+        stream: &[
+            0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff,
+        ],
+    },
+    Case {
+        name: "tuples",
+        hash: tuples,
+        stream: &[
+            0x01, 0x02, 0x00, 0x01, 0x78, 0x00, 0x00, 0x00, 0xfd, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0x00,
+        ],
+    },
+];
+
+/// Compares the written bytes with the expected stream.
+struct Compare {
+    expected: &'static [u8],
+    offset: usize,
+    mismatch: Option<usize>,
+}
+
+impl Hasher for Compare {
+    fn finish(&self) -> u64 {
+        0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        if self.mismatch.is_some() {
+            return;
+        }
+        let expected = self.expected.get(self.offset..).unwrap_or(&[]);
+        match bytes.iter().zip(expected).position(|(a, b)| a != b) {
+            Some(index) => self.mismatch = Some(self.offset + index),
+            None if bytes.len() > expected.len() => {
+                self.mismatch = Some(self.offset + expected.len())
+            }
+            None => self.offset += bytes.len(),
+        }
+    }
+}
+
+fn check_stream(case: &Case) -> Result<(), Mismatch> {
+    let mut compare = DeterministicHasher::new(Compare {
+        expected: case.stream,
+        offset: 0,
+        mismatch: None,
+    });
+    (case.hash)(&mut compare);
+    let compare = compare.into_inner();
+    let mismatch = match compare.mismatch {
+        None if compare.offset < case.stream.len() => Some(compare.offset),
+        mismatch => mismatch,
+    };
+    match mismatch {
+        Some(offset) => Err(Mismatch::Stream {
+            case: case.name,
+            offset,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Checks that this compiler and target hash the corpus to its canonical streams, and that the
+/// algorithm `A` returns its compiled-in output for them.
+pub fn self_check<A: SelfCheckAlgorithm>() -> Result<(), Mismatch> {
+    for case in CORPUS {
+        check_stream(case)?;
+    }
+
+    let mut hasher = DeterministicHasher::new(A::new_hasher());
+    for case in CORPUS {
+        (case.hash)(&mut hasher);
+    }
+    if hasher.finish_output() != A::CORPUS_OUTPUT {
+        return Err(Mismatch::Output {
+            algorithm: core::any::type_name::<A>(),
+        });
+    }
+    Ok(())
+}
+
+macro_rules! impl_self_check_algorithm {
+    ($feature:literal: $($algorithm:ty => $output:expr),* $(,)?) => {
+        $(
+            #[cfg(feature = $feature)]
+            impl SelfCheckAlgorithm for $algorithm {
+                const CORPUS_OUTPUT: Self::Output = $output;
+            }
+        )*
+    };
+}
+
+impl_self_check_algorithm!("blake3":
+    crate::blake3::Blake3Hasher => [
+        0xeb, 0xbf, 0x55, 0x5d, 0xdb, 0x15, 0xd5, 0x1e, 0x78, 0xc9, 0x73, 0x9a, 0xb3, 0xb8, 0x59, 0x23,
+        0xb9, 0x5b, 0xc6, 0x1b, 0x93, 0x3f, 0xe4, 0x56, 0x61, 0x7a, 0x03, 0x5d, 0x24, 0xcf, 0x11, 0xf0,
+    ],
+);
+impl_self_check_algorithm!("crc":
+    crate::crc::Crc16Ccitt => 0xebf9,
+    crate::crc::Crc32 => 0xf9fd_6a58,
+    crate::crc::Crc32c => 0x2a1b_a031,
+    crate::crc::Crc32Koopman => 0x158a_4b47,
+    crate::crc::Crc64Xz => 0x4b6c_9596_af27_a1f4,
+);
+impl_self_check_algorithm!("fnv":
+    crate::fnv::Fnv1a32 => 0x8084_c629,
+    crate::fnv::Fnv1a64 => 0xd39d_8216_2d8c_2ec9,
+);
+impl_self_check_algorithm!("murmur3":
+    crate::murmur3::Murmur3X86_32 => 0x2ffc_c1b6,
+    crate::murmur3::Murmur3X64_128 => 0x4925_1866_41e9_71a5_82d4_1184_6143_fc12,
+);
+impl_self_check_algorithm!("siphash":
+    crate::siphash::SipHasher13 => 0xb6a4_17cf_701b_6e19,
+    crate::siphash::SipHasher24 => 0x994d_0f10_6f7c_5286,
+);
+impl_self_check_algorithm!("xxhash":
+    crate::xxhash::Xxh32 => 0xb409_661d,
+    crate::xxhash::Xxh64 => 0x7954_a425_1a94_7ccb,
+);
+impl_self_check_algorithm!("xxh3":
+    crate::xxh3::Xxh3_64 => 0x0957_e801_9c79_ef1d,
+    crate::xxh3::Xxh3_128 => 0x3174_2d41_e78b_e839_0957_e801_9c79_ef1d,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::{check_stream, Case, Mismatch, CORPUS};
+    use core::hash::Hasher;
+
+    #[test]
+    fn built_in_algorithms() {
+        #[cfg(feature = "blake3")]
+        super::self_check::<crate::blake3::Blake3Hasher>().unwrap();
+        #[cfg(feature = "crc")]
+        {
+            super::self_check::<crate::crc::Crc16Ccitt>().unwrap();
+            super::self_check::<crate::crc::Crc32>().unwrap();
+            super::self_check::<crate::crc::Crc32c>().unwrap();
+            super::self_check::<crate::crc::Crc32Koopman>().unwrap();
+            super::self_check::<crate::crc::Crc64Xz>().unwrap();
+        }
+        #[cfg(feature = "fnv")]
+        {
+            super::self_check::<crate::fnv::Fnv1a32>().unwrap();
+            super::self_check::<crate::fnv::Fnv1a64>().unwrap();
+        }
+        #[cfg(feature = "murmur3")]
+        {
+            super::self_check::<crate::murmur3::Murmur3X86_32>().unwrap();
+            super::self_check::<crate::murmur3::Murmur3X64_128>().unwrap();
+        }
+        #[cfg(feature = "siphash")]
+        {
+            super::self_check::<crate::siphash::SipHasher13>().unwrap();
+            super::self_check::<crate::siphash::SipHasher24>().unwrap();
+        }
+        #[cfg(feature = "xxhash")]
+        {
+            super::self_check::<crate::xxhash::Xxh32>().unwrap();
+            super::self_check::<crate::xxhash::Xxh64>().unwrap();
+        }
+        #[cfg(feature = "xxh3")]
+        {
+            super::self_check::<crate::xxh3::Xxh3_64>().unwrap();
+            super::self_check::<crate::xxh3::Xxh3_128>().unwrap();
+        }
+    }
+
+    #[test]
+    fn stream_mismatches() {
+        fn case(stream: &'static [u8]) -> Case {
+            Case {
+                name: "test",
+                hash: |state: &mut dyn Hasher| state.write_u16(0x0201),
+                stream,
+            }
+        }
+        for case in CORPUS {
+            check_stream(case).unwrap();
+        }
+        check_stream(&case(&[1, 2])).unwrap();
+        let mismatch = |offset| {
+            Err(Mismatch::Stream {
+                case: "test",
+                offset,
+            })
+        };
+        assert_eq!(check_stream(&case(&[1, 3])), mismatch(1));
+        assert_eq!(check_stream(&case(&[1])), mismatch(1));
+        assert_eq!(check_stream(&case(&[1, 2, 3])), mismatch(2));
+        assert_eq!(check_stream(&case(&[])), mismatch(0));
+    }
+}
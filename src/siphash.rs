@@ -0,0 +1,239 @@
+//! The SipHash-1-3 and SipHash-2-4 hash functions.
+//!
+//! `std::collections::hash_map::DefaultHasher::new()` is SipHash-1-3 with both keys set to zero.
+//! Because `DeterministicHasher` writes the same bytes as `std` does on little-endian 64-bit
+//! platforms, `DeterministicSipHasher13::default()` reproduces the hashes that `DefaultHasher`
+//! computes on such platforms, on every architecture. This does not hold for strings with the
+//! `nightly` feature, which frames them differently than `std`, nor for slices of integers wider
+//! than `u8`, like `Vec<u32>` or `&[u16]`, which `core` writes as their native memory: on a 32-bit
+//! or big-endian target those feed other bytes than `std` did on x64. Hash such data with
+//! `StableHash` instead. Note that `std` does not guarantee that `DefaultHasher` stays SipHash-1-3
+//! in future releases.
+//!
+//! ```
+//! use core::hash::{Hash, Hasher};
+//! use deterministic_hash::siphash::DeterministicSipHasher13;
+//! let mut hasher = DeterministicSipHasher13::default();
+//! (0x1337 as usize).hash(&mut hasher);
+//! assert_eq!(hasher.finish(), 0xd94d5f450b09cee1);
+//! ```
+
+use crate::block::{read_u64, BlockBuffer};
+use crate::{
+    BoundaryIndependent, DeterministicBuildHasher, DeterministicHasher, FixedOutputHasher,
+    HashAlgorithm,
+};
+use core::hash::{BuildHasherDefault, Hasher};
+
+/// SipHash with `C` compression rounds and `D` finalization rounds.
+#[derive(Clone, Debug)]
+pub struct SipHasher<const C: usize, const D: usize> {
+    v: [u64; 4],
+    blocks: BlockBuffer<8>,
+}
+
+/// The SipHash-1-3 hasher, as used by `std`.
+pub type SipHasher13 = SipHasher<1, 3>;
+
+/// The SipHash-2-4 hasher, as described in the original paper.
+pub type SipHasher24 = SipHasher<2, 4>;
+
+/// `DeterministicHasher` around the SipHash-1-3 hasher.
+pub type DeterministicSipHasher13 = DeterministicHasher<SipHasher13>;
+
+/// `DeterministicBuildHasher` that builds `DeterministicSipHasher13` hashers.
+pub type BuildDeterministicSipHasher13 = DeterministicBuildHasher<BuildHasherDefault<SipHasher13>>;
+
+/// `DeterministicHasher` around the SipHash-2-4 hasher.
+pub type DeterministicSipHasher24 = DeterministicHasher<SipHasher24>;
+
+/// `DeterministicBuildHasher` that builds `DeterministicSipHasher24` hashers.
+pub type BuildDeterministicSipHasher24 = DeterministicBuildHasher<BuildHasherDefault<SipHasher24>>;
+
+impl<const C: usize, const D: usize> SipHasher<C, D> {
+    /// Creates a hasher with both keys set to zero, like `DefaultHasher::new()`.
+    pub const fn new() -> Self {
+        Self::new_with_keys(0, 0)
+    }
+
+    pub const fn new_with_keys(k0: u64, k1: u64) -> Self {
+        Self {
+            v: [
+                k0 ^ 0x736f_6d65_7073_6575,
+                k1 ^ 0x646f_7261_6e64_6f6d,
+                k0 ^ 0x6c79_6765_6e65_7261,
+                k1 ^ 0x7465_6462_7974_6573,
+            ],
+            blocks: BlockBuffer::new(),
+        }
+    }
+
+    fn rounds(v: &mut [u64; 4], rounds: usize) {
+        for _ in 0..rounds {
+            v[0] = v[0].wrapping_add(v[1]);
+            v[1] = v[1].rotate_left(13) ^ v[0];
+            v[0] = v[0].rotate_left(32);
+            v[2] = v[2].wrapping_add(v[3]);
+            v[3] = v[3].rotate_left(16) ^ v[2];
+            v[0] = v[0].wrapping_add(v[3]);
+            v[3] = v[3].rotate_left(21) ^ v[0];
+            v[2] = v[2].wrapping_add(v[1]);
+            v[1] = v[1].rotate_left(17) ^ v[2];
+            v[2] = v[2].rotate_left(32);
+        }
+    }
+
+    fn compress(v: &mut [u64; 4], m: u64) {
+        v[3] ^= m;
+        Self::rounds(v, C);
+        v[0] ^= m;
+    }
+}
+
+impl<const C: usize, const D: usize> Default for SipHasher<C, D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const C: usize, const D: usize> Hasher for SipHasher<C, D> {
+    fn finish(&self) -> u64 {
+        let mut v = self.v;
+        let mut b = self.blocks.len() << 56;
+        for (i, byte) in self.blocks.tail().iter().enumerate() {
+            b |= (*byte as u64) << (8 * i);
+        }
+        Self::compress(&mut v, b);
+        v[2] ^= 0xff;
+        Self::rounds(&mut v, D);
+        v[0] ^ v[1] ^ v[2] ^ v[3]
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        let v = &mut self.v;
+        self.blocks
+            .write(bytes, |block| Self::compress(v, read_u64(block)));
+    }
+}
+
+impl<const C: usize, const D: usize> FixedOutputHasher for SipHasher<C, D> {
+    type Output = u64;
+
+    fn finish_output(&self) -> u64 {
+        self.finish()
+    }
+}
+
+/// Uses both keys set to zero, like `DefaultHasher::new()`.
+impl<const C: usize, const D: usize> HashAlgorithm for SipHasher<C, D> {
+    fn new_hasher() -> Self {
+        Self::new()
+    }
+}
+
+impl<const C: usize, const D: usize> BoundaryIndependent for SipHasher<C, D> {}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{SipHasher13, SipHasher24};
+    use crate::testing::assert_boundary_independent;
+    use core::hash::Hasher;
+
+    /// The key of the reference vectors of the SipHash paper.
+    const KEY: (u64, u64) = (0x0706050403020100, 0x0f0e0d0c0b0a0908);
+
+    /// SipHash-2-4 and SipHash-1-3 of the bytes `0..len` under `KEY`.
+    const VECTORS: [(u64, u64); 16] = [
+        (0x726fdb47dd0e0e31, 0xabac0158050fc4dc),
+        (0x74f839c593dc67fd, 0xc9f49bf37d57ca93),
+        (0x0d6c8009d9a94f5a, 0x82cb9b024dc7d44d),
+        (0x85676696d7fb7e2d, 0x8bf80ab8e7ddf7fb),
+        (0xcf2794e0277187b7, 0xcf75576088d38328),
+        (0x18765564cd99a68d, 0xdef9d52f49533b67),
+        (0xcbc9466e58fee3ce, 0xc50d2b50c59f22a7),
+        (0xab0200f58b01d137, 0xd3927d989bb11140),
+        (0x93f5f5799a932462, 0x369095118d299a8e),
+        (0x9e0082df0ba9e4b0, 0x25a48eb36c063de4),
+        (0x7a5dbbc594ddb9f3, 0x79de85ee92ff097f),
+        (0xf4b32f46226bada7, 0x70c118c1f94dc352),
+        (0x751e8fbc860ee5fb, 0x78a384b157b4d9a2),
+        (0x14ea5627c0843d90, 0x306f760c1229ffa7),
+        (0xf723ca908e7af2ee, 0x605aa111c0f95d34),
+        (0xa129ca6149be45e5, 0xd320d86d2a519956),
+    ];
+
+    #[test]
+    fn reference_vectors() {
+        let input: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+        for (len, (expected24, expected13)) in VECTORS.iter().enumerate() {
+            for chunk_size in 1..=9 {
+                let mut sip24 = SipHasher24::new_with_keys(KEY.0, KEY.1);
+                let mut sip13 = SipHasher13::new_with_keys(KEY.0, KEY.1);
+                for chunk in input[..len].chunks(chunk_size) {
+                    sip24.write(chunk);
+                    sip13.write(chunk);
+                }
+                assert_eq!(sip24.finish(), *expected24, "length {}", len);
+                assert_eq!(sip13.finish(), *expected13, "length {}", len);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(all(
+        target_endian = "little",
+        target_pointer_width = "64",
+        not(feature = "nightly")
+    ))]
+    fn matches_std_default_hasher() {
+        use super::DeterministicSipHasher13;
+        use core::hash::Hash;
+        use std::collections::hash_map::DefaultHasher;
+        use std::string::String;
+        use std::vec;
+        use std::vec::Vec;
+
+        #[derive(Hash)]
+        struct Record {
+            id: usize,
+            offset: isize,
+            name: String,
+            values: Vec<u32>,
+            wide: u128,
+            parent: Option<u16>,
+        }
+
+        let record = Record {
+            id: 0x1337,
+            offset: -42,
+            name: String::from("deterministic"),
+            values: vec![1, 2, 3],
+            wide: u128::MAX - 1,
+            parent: Some(7),
+        };
+
+        let mut std_hasher = DefaultHasher::new();
+        record.hash(&mut std_hasher);
+        let mut hasher = DeterministicSipHasher13::default();
+        record.hash(&mut hasher);
+        assert_eq!(hasher.finish(), std_hasher.finish());
+
+        // `values` is written as its native memory, so this part of the hash differs per target.
+        let mut native = DeterministicSipHasher13::default();
+        record.values.hash(&mut native);
+        let mut bytes = DeterministicSipHasher13::default();
+        bytes.write_usize(record.values.len());
+        for value in record.values.iter() {
+            bytes.write(&value.to_ne_bytes());
+        }
+        assert_eq!(native.finish(), bytes.finish());
+    }
+
+    #[test]
+    fn boundary_independent() {
+        assert_boundary_independent::<SipHasher13>();
+        assert_boundary_independent::<SipHasher24>();
+    }
+}
@@ -0,0 +1,307 @@
+//! Snapshot tests that pin the hashes of values, to catch changes by refactors or Rust upgrades.
+//!
+//! `assert_hash_stable!(name, value)` hashes the value with every listed algorithm, FNV-1a 64 and
+//! SipHash-1-3 by default, and compares the outputs with the snapshot file `snapshots/<name>.txt`
+//! in the directory of the manifest of the crate under test. Check the snapshot files in, such
+//! that a changed hash fails the test.
+//!
+//! Run the tests with the environment variable `DETERMINISTIC_HASH_UPDATE_SNAPSHOTS=1` to create
+//! missing snapshot files and to overwrite changed ones.
+//!
+//! ```no_run
+//! use deterministic_hash::assert_hash_stable;
+//! use deterministic_hash::siphash::SipHasher24;
+//!
+//! assert_hash_stable!("header", (1u8, 0x1337u32, [4u8, 5, 6]));
+//! assert_hash_stable!("header-siphash24", (1u8, 0x1337u32, [4u8, 5, 6]), SipHasher24);
+//! ```
+//!
+//! A snapshot file has a line per algorithm, with the name of the algorithm and its output in
+//! hexadecimal, like `snapshots/header.txt` of the first assertion above:
+//!
+//! ```text
+//! fnv1a64 be1da0c65198f94c
+//! siphash13 a8cf3f0f06b8a6d8
+//! ```
+
+use crate::{hash_one, HashAlgorithm};
+use core::hash::Hash;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::string::String;
+use std::vec::Vec;
+use std::{env, format, fs};
+
+/// The environment variable that makes `assert_hash_stable!` write its snapshot files.
+pub const UPDATE_VAR: &str = "DETERMINISTIC_HASH_UPDATE_SNAPSHOTS";
+
+/// A hash algorithm with a name, which identifies its output in snapshot files.
+pub trait SnapshotAlgorithm: HashAlgorithm {
+    const NAME: &'static str;
+}
+
+/// An output of a hash algorithm, written in hexadecimal.
+pub trait ToHex {
+    fn to_hex(&self) -> String;
+}
+
+macro_rules! impl_to_hex_int {
+    ($($int:ty),*) => {
+        $(
+            /// Writes all digits of the integer, most significant first.
+            impl ToHex for $int {
+                fn to_hex(&self) -> String {
+                    format!("{:01$x}", self, 2 * core::mem::size_of::<$int>())
+                }
+            }
+        )*
+    };
+}
+
+impl_to_hex_int!(u16, u32, u64, u128);
+
+/// Writes the bytes in order.
+impl<const N: usize> ToHex for [u8; N] {
+    fn to_hex(&self) -> String {
+        let mut hex = String::with_capacity(2 * N);
+        for byte in self.iter() {
+            write!(hex, "{:02x}", byte).unwrap();
+        }
+        hex
+    }
+}
+
+macro_rules! impl_snapshot_algorithm {
+    ($feature:literal: $($algorithm:ty => $name:literal),* $(,)?) => {
+        $(
+            #[cfg(feature = $feature)]
+            impl SnapshotAlgorithm for $algorithm {
+                const NAME: &'static str = $name;
+            }
+        )*
+    };
+}
+
+impl_snapshot_algorithm!("blake3": crate::blake3::Blake3Hasher => "blake3");
+impl_snapshot_algorithm!("crc":
+    crate::crc::Crc16Ccitt => "crc16_ccitt",
+    crate::crc::Crc32 => "crc32",
+    crate::crc::Crc32c => "crc32c",
+    crate::crc::Crc32Koopman => "crc32_koopman",
+    crate::crc::Crc64Xz => "crc64_xz",
+);
+impl_snapshot_algorithm!("fnv":
+    crate::fnv::Fnv1a32 => "fnv1a32",
+    crate::fnv::Fnv1a64 => "fnv1a64",
+);
+impl_snapshot_algorithm!("murmur3":
+    crate::murmur3::Murmur3X86_32 => "murmur3_x86_32",
+    crate::murmur3::Murmur3X64_128 => "murmur3_x64_128",
+);
+impl_snapshot_algorithm!("siphash":
+    crate::siphash::SipHasher13 => "siphash13",
+    crate::siphash::SipHasher24 => "siphash24",
+);
+impl_snapshot_algorithm!("xxhash":
+    crate::xxhash::Xxh32 => "xxh32",
+    crate::xxhash::Xxh64 => "xxh64",
+);
+impl_snapshot_algorithm!("xxh3":
+    crate::xxh3::Xxh3_64 => "xxh3_64",
+    crate::xxh3::Xxh3_128 => "xxh3_128",
+);
+
+/// Hashes the value through a `DeterministicHasher`, and returns the name of the algorithm with
+/// the output in hexadecimal.
+pub fn hash<A, T>(value: &T) -> (&'static str, String)
+where
+    A: SnapshotAlgorithm,
+    A::Output: ToHex,
+    T: Hash + ?Sized,
+{
+    (A::NAME, hash_one::<A, T>(value).to_hex())
+}
+
+/// Compares the hashes with the snapshot `name` in the `snapshots` directory of the manifest
+/// directory, see `assert_hash_stable!`.
+///
+/// Panics when the snapshot is missing or differs, unless `DETERMINISTIC_HASH_UPDATE_SNAPSHOTS` is
+/// set, in which case the snapshot is written.
+pub fn assert_stable(manifest_dir: &str, name: &str, hashes: &[(&'static str, String)]) {
+    let update = env::var_os(UPDATE_VAR).is_some_and(|value| !value.is_empty() && value != "0");
+    let dir = Path::new(manifest_dir).join("snapshots");
+    if let Err(message) = check(&dir, name, hashes, update) {
+        panic!("{}", message);
+    }
+}
+
+fn check(
+    dir: &Path,
+    name: &str,
+    hashes: &[(&'static str, String)],
+    update: bool,
+) -> Result<(), String> {
+    let valid = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.';
+    if name.is_empty() || name.starts_with('.') || !name.chars().all(valid) {
+        return Err(format!(
+            "snapshot name {:?} is not a valid file name, use letters, digits, `-`, `_` and `.`",
+            name
+        ));
+    }
+
+    let path = dir.join(format!("{}.txt", name));
+    let mut snapshot = String::new();
+    for (algorithm, hex) in hashes.iter() {
+        writeln!(snapshot, "{} {}", algorithm, hex).unwrap();
+    }
+
+    let stored = match fs::read_to_string(&path) {
+        Ok(stored) => Some(stored),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+        Err(error) => {
+            return Err(format!(
+                "cannot read snapshot {}: {}",
+                path.display(),
+                error
+            ))
+        }
+    };
+    if stored.as_deref() == Some(snapshot.as_str()) {
+        return Ok(());
+    }
+
+    if update {
+        return fs::create_dir_all(dir)
+            .and_then(|_| fs::write(&path, snapshot))
+            .map_err(|error| format!("cannot write snapshot {}: {}", path.display(), error));
+    }
+
+    let stored = match stored {
+        Some(stored) => stored,
+        None => {
+            return Err(format!(
+                "snapshot {} does not exist, run the tests with {}=1 to create it",
+                path.display(),
+                UPDATE_VAR
+            ))
+        }
+    };
+
+    let stored: Vec<(&str, &str)> = stored
+        .lines()
+        .filter_map(|line| {
+            let mut words = line.split_whitespace();
+            Some((words.next()?, words.next().unwrap_or("")))
+        })
+        .collect();
+    let mut message = format!("hash of snapshot `{}` changed:", name);
+    for (algorithm, hex) in hashes.iter() {
+        match stored.iter().find(|(stored, _)| stored == algorithm) {
+            Some((_, stored)) if stored == hex => {}
+            Some((_, stored)) => {
+                write!(message, "\n  {}: {} -> {}", algorithm, stored, hex).unwrap()
+            }
+            None => write!(message, "\n  {}: missing -> {}", algorithm, hex).unwrap(),
+        }
+    }
+    for (algorithm, stored) in stored.iter() {
+        if !hashes.iter().any(|(name, _)| name == algorithm) {
+            write!(message, "\n  {}: {} -> not checked", algorithm, stored).unwrap();
+        }
+    }
+    write!(
+        message,
+        "\nrun the tests with {}=1 to accept the new hashes of {}",
+        UPDATE_VAR,
+        path.display()
+    )
+    .unwrap();
+    Err(message)
+}
+
+/// Asserts that the hashes of a value match its snapshot file, see the `snapshot` module.
+///
+/// Hashes with FNV-1a 64 and SipHash-1-3 by default, or with the listed algorithms that implement
+/// `snapshot::SnapshotAlgorithm`.
+///
+/// The value is hashed with its `Hash` impl, and `core` writes slices and arrays of integers wider
+/// than `u8`, like `[usize]` or `Vec<u32>`, as their native memory. The snapshot of such a value
+/// differs between 32 and 64-bit and between little and big-endian targets, so pin their elements
+/// or `u8` slices instead.
+#[macro_export]
+macro_rules! assert_hash_stable {
+    ($name:expr, $value:expr $(,)?) => {
+        $crate::assert_hash_stable!(
+            $name,
+            $value,
+            $crate::fnv::Fnv1a64,
+            $crate::siphash::SipHasher13
+        )
+    };
+    ($name:expr, $value:expr, $($algorithm:ty),+ $(,)?) => {{
+        let value = &$value;
+        $crate::snapshot::assert_stable(
+            env!("CARGO_MANIFEST_DIR"),
+            $name,
+            &[$($crate::snapshot::hash::<$algorithm, _>(value)),+],
+        )
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, hash, ToHex};
+    use crate::fnv::Fnv1a64;
+    use crate::siphash::SipHasher13;
+    use std::{format, fs, process};
+
+    #[test]
+    fn to_hex() {
+        assert_eq!(0xabu16.to_hex(), "00ab");
+        assert_eq!(0x1337u32.to_hex(), "00001337");
+        assert_eq!(1u128.to_hex(), "00000000000000000000000000000001");
+        assert_eq!([0x01u8, 0xef].to_hex(), "01ef");
+    }
+
+    #[test]
+    fn check_and_update() {
+        let dir = std::env::temp_dir().join(format!("deterministic-hash-{}", process::id()));
+        let hashes = [hash::<Fnv1a64, _>(&1u32), hash::<SipHasher13, _>(&1u32)];
+        let changed = [hash::<Fnv1a64, _>(&2u32), hash::<SipHasher13, _>(&1u32)];
+
+        let missing = check(&dir, "value", &hashes, false).unwrap_err();
+        assert!(missing.contains("does not exist"), "{}", missing);
+        check(&dir, "value", &hashes, true).unwrap();
+        check(&dir, "value", &hashes, false).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join("value.txt")).unwrap(),
+            format!("fnv1a64 {}\nsiphash13 {}\n", hashes[0].1, hashes[1].1)
+        );
+
+        let message = check(&dir, "value", &changed, false).unwrap_err();
+        assert!(
+            message.contains(&format!("fnv1a64: {} -> {}", hashes[0].1, changed[0].1)),
+            "{}",
+            message
+        );
+        assert!(!message.contains("siphash13:"), "{}", message);
+        let message = check(&dir, "value", &hashes[..1], false).unwrap_err();
+        assert!(message.contains("siphash13: "), "{}", message);
+        assert!(message.contains("-> not checked"), "{}", message);
+
+        check(&dir, "value", &changed, true).unwrap();
+        check(&dir, "value", &changed, false).unwrap();
+        assert!(check(&dir, "../value", &hashes, true).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn pinned() {
+        assert_hash_stable!("integers", (1u8, -2i16, 0x1337u32, u64::MAX, -5i128));
+        assert_hash_stable!("usize", (0x1337usize, -1isize, (1usize, 2usize, 3usize)));
+        assert_hash_stable!(
+            "options",
+            (Some(1u32), None::<u64>, Ok::<u8, i8>(3), true, 'x')
+        );
+    }
+}
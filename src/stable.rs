@@ -0,0 +1,499 @@
+//! Hashing with a byte encoding that is defined by this library rather than by `core`.
+//!
+//! `core::hash::Hash` leaves a lot of encoding choices to the standard library: slices of integers
+//! such as `[u32]` or `[usize]` are written as their native memory in a single `Hasher::write` call,
+//! strings are terminated with `0xFF`, and how lengths and enum discriminants are written may change
+//! between Rust releases. `StableHash` writes every value with the documented encoding below,
+//! through `Hasher::write` only, so it can feed any hasher, including `DeterministicHasher`.
+//!
+//! # Encoding version 1
+//!
+//! * `u8`, `u16`, `u32`, `u64`, `u128` and their signed counterparts are written as their
+//!   little-endian bytes. `usize` is written as a `u64` and `isize` as an `i64`.
+//! * `bool` is written as a single byte, `0` or `1`. `char` is written as a `u32`.
+//! * Lengths are written as a `u64`. `str` is written as its length in bytes followed by its UTF-8
+//!   bytes. Slices, arrays and sequences are written as their length followed by every element.
+//!   Maps are written as their length followed by every key and value, in ascending key order.
+//! * Enum variants are written as a `u32` tag followed by their fields. `Option` uses tag `0` for
+//!   `None` and `1` for `Some`, `Result` uses tag `0` for `Ok` and `1` for `Err`.
+//! * Tuples and structs are written as their fields in order, without any framing. `()` and
+//!   `PhantomData` write nothing.
+//! * Tagged structs are written as a `u32` tag followed by the value of every field that differs
+//!   from its default, in ascending tag order, followed by the tag `0`.
+//! * `core::cmp::Ordering` is written as an `i8`, `Duration` as its seconds as a `u64` followed by
+//!   its subsecond nanoseconds as a `u32`, and ranges as their start followed by their end.
+//! * References, `NonZero*`, `Wrapping`, `Reverse` and smart pointers are written as the value they
+//!   wrap.
+//!
+//! The encoding will only ever change together with `ENCODING_VERSION`.
+//!
+//! With the `alloc` feature `StableHash` is also implemented for `Box`, `Rc`, `Arc`, `Cow`,
+//! `String`, `Vec`, `VecDeque`, `LinkedList`, `BTreeMap` and `BTreeSet`.
+//!
+//! ```
+//! use deterministic_hash::{DeterministicHasher, StableHash};
+//! use crc::crc32::Hasher32;
+//! let mut hasher = DeterministicHasher::new(crc::crc32::Digest::new(crc::crc32::KOOPMAN));
+//! [1usize, 2, 3][..].stable_hash(&mut hasher);
+//! assert_eq!(hasher.as_inner().sum32(), 652052568);
+//! ```
+
+use core::cmp::{Ordering, Reverse};
+use core::hash::Hasher;
+use core::marker::PhantomData;
+use core::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize, Wrapping,
+};
+use core::ops::{Range, RangeInclusive};
+use core::time::Duration;
+
+/// The version of the byte encoding written by `StableHash`.
+pub const ENCODING_VERSION: u32 = 1;
+
+/// A value that can be hashed with a byte encoding that is identical on every target.
+///
+/// The bytes are written with `Hasher::write` only, so any hasher can be used.
+pub trait StableHash {
+    /// Feeds this value into the given hasher.
+    fn stable_hash<H: Hasher>(&self, state: &mut H);
+
+    /// Feeds a slice of this type into the given hasher, without the length prefix.
+    fn stable_hash_slice<H: Hasher>(data: &[Self], state: &mut H)
+    where
+        Self: Sized,
+    {
+        for piece in data {
+            piece.stable_hash(state);
+        }
+    }
+}
+
+/// Writes the length of a collection as a `u64`.
+pub fn write_length<H: Hasher>(state: &mut H, len: usize) {
+    state.write(&(len as u64).to_le_bytes());
+}
+
+/// Writes the tag of an enum variant or of a field of a tagged struct as a `u32`.
+pub fn write_tag<H: Hasher>(state: &mut H, tag: u32) {
+    state.write(&tag.to_le_bytes());
+}
+
+/// Returns whether the value equals its default, in which case a tagged struct leaves it out.
+pub fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+macro_rules! impl_stable_hash_int {
+    ($($ty:ty)*) => {$(
+        impl StableHash for $ty {
+            fn stable_hash<H: Hasher>(&self, state: &mut H) {
+                state.write(&self.to_le_bytes());
+            }
+        }
+    )*};
+}
+
+impl_stable_hash_int!(u16 u32 u64 u128 i8 i16 i32 i64 i128);
+
+impl StableHash for u8 {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        state.write(&[*self]);
+    }
+
+    fn stable_hash_slice<H: Hasher>(data: &[Self], state: &mut H) {
+        state.write(data);
+    }
+}
+
+impl StableHash for usize {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        (*self as u64).stable_hash(state);
+    }
+}
+
+impl StableHash for isize {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        (*self as i64).stable_hash(state);
+    }
+}
+
+impl StableHash for bool {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        (*self as u8).stable_hash(state);
+    }
+}
+
+impl StableHash for char {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        (*self as u32).stable_hash(state);
+    }
+}
+
+impl StableHash for str {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().stable_hash(state);
+    }
+}
+
+impl<T: StableHash> StableHash for [T] {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        write_length(state, self.len());
+        T::stable_hash_slice(self, state);
+    }
+}
+
+impl<T: StableHash, const N: usize> StableHash for [T; N] {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        self[..].stable_hash(state);
+    }
+}
+
+impl<T: StableHash + ?Sized> StableHash for &T {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        (**self).stable_hash(state);
+    }
+}
+
+impl<T: StableHash + ?Sized> StableHash for &mut T {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        (**self).stable_hash(state);
+    }
+}
+
+impl<T: ?Sized> StableHash for PhantomData<T> {
+    fn stable_hash<H: Hasher>(&self, _state: &mut H) {}
+}
+
+impl<T: StableHash> StableHash for Option<T> {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            None => write_tag(state, 0),
+            Some(value) => {
+                write_tag(state, 1);
+                value.stable_hash(state);
+            }
+        }
+    }
+}
+
+impl<T: StableHash, E: StableHash> StableHash for Result<T, E> {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Ok(value) => {
+                write_tag(state, 0);
+                value.stable_hash(state);
+            }
+            Err(error) => {
+                write_tag(state, 1);
+                error.stable_hash(state);
+            }
+        }
+    }
+}
+
+impl StableHash for Ordering {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        (*self as i8).stable_hash(state);
+    }
+}
+
+impl StableHash for Duration {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        self.as_secs().stable_hash(state);
+        self.subsec_nanos().stable_hash(state);
+    }
+}
+
+impl<T: StableHash> StableHash for Range<T> {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        self.start.stable_hash(state);
+        self.end.stable_hash(state);
+    }
+}
+
+impl<T: StableHash> StableHash for RangeInclusive<T> {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        self.start().stable_hash(state);
+        self.end().stable_hash(state);
+    }
+}
+
+impl<T: StableHash> StableHash for Wrapping<T> {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        self.0.stable_hash(state);
+    }
+}
+
+impl<T: StableHash> StableHash for Reverse<T> {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        self.0.stable_hash(state);
+    }
+}
+
+macro_rules! impl_stable_hash_non_zero {
+    ($($ty:ty)*) => {$(
+        impl StableHash for $ty {
+            fn stable_hash<H: Hasher>(&self, state: &mut H) {
+                self.get().stable_hash(state);
+            }
+        }
+    )*};
+}
+
+impl_stable_hash_non_zero!(
+    NonZeroU8 NonZeroU16 NonZeroU32 NonZeroU64 NonZeroU128 NonZeroUsize
+    NonZeroI8 NonZeroI16 NonZeroI32 NonZeroI64 NonZeroI128 NonZeroIsize
+);
+
+macro_rules! impl_stable_hash_tuple {
+    ($(($($name:ident)*))*) => {$(
+        impl<$($name: StableHash),*> StableHash for ($($name,)*) {
+            #[allow(non_snake_case, unused_variables)]
+            fn stable_hash<H: Hasher>(&self, state: &mut H) {
+                let ($($name,)*) = self;
+                $($name.stable_hash(state);)*
+            }
+        }
+    )*};
+}
+
+impl_stable_hash_tuple!(
+    ()
+    (A)
+    (A B)
+    (A B C)
+    (A B C D)
+    (A B C D E)
+    (A B C D E F)
+    (A B C D E F G)
+    (A B C D E F G I)
+    (A B C D E F G I J)
+    (A B C D E F G I J K)
+    (A B C D E F G I J K L)
+    (A B C D E F G I J K L M)
+);
+
+#[cfg(feature = "alloc")]
+mod collections {
+    use super::{write_length, StableHash};
+    use alloc::borrow::{Cow, ToOwned};
+    use alloc::boxed::Box;
+    use alloc::collections::{BTreeMap, BTreeSet, LinkedList, VecDeque};
+    use alloc::rc::Rc;
+    use alloc::string::String;
+    use alloc::sync::Arc;
+    use alloc::vec::Vec;
+    use core::hash::Hasher;
+
+    macro_rules! impl_stable_hash_deref {
+        ($($ty:ident)*) => {$(
+            impl<T: StableHash + ?Sized> StableHash for $ty<T> {
+                fn stable_hash<H: Hasher>(&self, state: &mut H) {
+                    (**self).stable_hash(state);
+                }
+            }
+        )*};
+    }
+
+    impl_stable_hash_deref!(Box Rc Arc);
+
+    impl<T: StableHash + ToOwned + ?Sized> StableHash for Cow<'_, T> {
+        fn stable_hash<H: Hasher>(&self, state: &mut H) {
+            (**self).stable_hash(state);
+        }
+    }
+
+    impl StableHash for String {
+        fn stable_hash<H: Hasher>(&self, state: &mut H) {
+            self.as_str().stable_hash(state);
+        }
+    }
+
+    impl<T: StableHash> StableHash for Vec<T> {
+        fn stable_hash<H: Hasher>(&self, state: &mut H) {
+            self.as_slice().stable_hash(state);
+        }
+    }
+
+    impl<T: StableHash> StableHash for VecDeque<T> {
+        fn stable_hash<H: Hasher>(&self, state: &mut H) {
+            write_length(state, self.len());
+            let (front, back) = self.as_slices();
+            T::stable_hash_slice(front, state);
+            T::stable_hash_slice(back, state);
+        }
+    }
+
+    impl<T: StableHash> StableHash for LinkedList<T> {
+        fn stable_hash<H: Hasher>(&self, state: &mut H) {
+            write_length(state, self.len());
+            for element in self {
+                element.stable_hash(state);
+            }
+        }
+    }
+
+    impl<T: StableHash> StableHash for BTreeSet<T> {
+        fn stable_hash<H: Hasher>(&self, state: &mut H) {
+            write_length(state, self.len());
+            for element in self {
+                element.stable_hash(state);
+            }
+        }
+    }
+
+    impl<K: StableHash, V: StableHash> StableHash for BTreeMap<K, V> {
+        fn stable_hash<H: Hasher>(&self, state: &mut H) {
+            write_length(state, self.len());
+            for (key, value) in self {
+                key.stable_hash(state);
+                value.stable_hash(state);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::StableHash;
+    use crate::testing::Bytes;
+    use std::vec::Vec;
+
+    fn bytes<T: StableHash + ?Sized>(value: &T) -> Vec<u8> {
+        let mut hasher = Bytes::default();
+        value.stable_hash(&mut hasher);
+        hasher.0
+    }
+
+    const LEN_2: [u8; 8] = [2, 0, 0, 0, 0, 0, 0, 0];
+
+    fn prefixed(elements: &[u8]) -> Vec<u8> {
+        let mut expected = LEN_2.to_vec();
+        expected.extend_from_slice(elements);
+        expected
+    }
+
+    #[test]
+    fn unsigned_slices() {
+        assert_eq!(bytes(&[0x01u8, 0xFE][..]), prefixed(&[0x01, 0xFE]));
+        assert_eq!(
+            bytes(&[0x0102u16, 0xFEFF][..]),
+            prefixed(&[0x02, 0x01, 0xFF, 0xFE])
+        );
+        assert_eq!(
+            bytes(&[0x01020304u32, 0xFEFF][..]),
+            prefixed(&[0x04, 0x03, 0x02, 0x01, 0xFF, 0xFE, 0, 0])
+        );
+        assert_eq!(
+            bytes(&[0x0102030405060708u64, 1][..]),
+            prefixed(&[8, 7, 6, 5, 4, 3, 2, 1, 1, 0, 0, 0, 0, 0, 0, 0])
+        );
+        assert_eq!(
+            bytes(&[1u128 << 120 | 2, u128::MAX][..]),
+            prefixed(&[
+                2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, //
+                0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, //
+                0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+            ])
+        );
+        assert_eq!(
+            bytes(&[0x1337usize, 0xFFFF_FFFF][..]),
+            prefixed(&[0x37, 0x13, 0, 0, 0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0])
+        );
+    }
+
+    #[test]
+    fn signed_slices() {
+        assert_eq!(bytes(&[-1i8, 2][..]), prefixed(&[0xFF, 0x02]));
+        assert_eq!(bytes(&[-2i16, 2][..]), prefixed(&[0xFE, 0xFF, 0x02, 0x00]));
+        assert_eq!(
+            bytes(&[-2i32, 2][..]),
+            prefixed(&[0xFE, 0xFF, 0xFF, 0xFF, 0x02, 0, 0, 0])
+        );
+        assert_eq!(
+            bytes(&[-2i64, 2][..]),
+            prefixed(&[0xFE, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 2, 0, 0, 0, 0, 0, 0, 0])
+        );
+        assert_eq!(
+            bytes(&[-2i128, 2][..]),
+            prefixed(&[
+                0xFE, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, //
+                0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, //
+                2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            ])
+        );
+        assert_eq!(
+            bytes(&[-2isize, 2][..]),
+            prefixed(&[0xFE, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 2, 0, 0, 0, 0, 0, 0, 0])
+        );
+    }
+
+    #[test]
+    fn char_and_bool_slices() {
+        assert_eq!(
+            bytes(&['a', '\u{1F980}'][..]),
+            prefixed(&[0x61, 0, 0, 0, 0x80, 0xF9, 0x01, 0x00])
+        );
+        assert_eq!(bytes(&[true, false][..]), prefixed(&[1, 0]));
+    }
+
+    #[test]
+    fn strings_are_length_prefixed() {
+        assert_eq!(bytes("ab"), prefixed(b"ab"));
+        assert_eq!(bytes(""), [0; 8]);
+    }
+
+    #[test]
+    fn enums_are_tagged() {
+        assert_eq!(bytes(&None::<u8>), [0, 0, 0, 0]);
+        assert_eq!(bytes(&Some(7u8)), [1, 0, 0, 0, 7]);
+        assert_eq!(bytes(&Ok::<u8, u16>(7)), [0, 0, 0, 0, 7]);
+        assert_eq!(bytes(&Err::<u8, u16>(7)), [1, 0, 0, 0, 7, 0]);
+        assert_eq!(bytes(&core::cmp::Ordering::Less), [0xFF]);
+    }
+
+    #[test]
+    fn tuples_are_concatenated() {
+        assert_eq!(bytes(&()), []);
+        assert_eq!(bytes(&(1u8, 2u16, (true,))), [1, 2, 0, 1]);
+        assert_eq!(
+            bytes(&core::time::Duration::new(1, 2)),
+            [1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0]
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn collections_match_slices() {
+        use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+        use alloc::string::String;
+        use alloc::vec;
+
+        assert_eq!(bytes(&vec![1usize, 2]), bytes(&[1usize, 2][..]));
+        assert_eq!(bytes(&String::from("ab")), bytes("ab"));
+
+        let mut deque = VecDeque::from(vec![2u32, 3]);
+        deque.push_front(1);
+        assert_eq!(bytes(&deque), bytes(&[1u32, 2, 3]));
+
+        let set: BTreeSet<u8> = [3, 1, 2].iter().copied().collect();
+        assert_eq!(bytes(&set), bytes(&[1u8, 2, 3]));
+
+        let map: BTreeMap<u8, bool> = [(2, false), (1, true)].iter().copied().collect();
+        assert_eq!(bytes(&map), prefixed(&[1, 1, 2, 0]));
+    }
+
+    #[test]
+    fn arrays_match_slices() {
+        assert_eq!(bytes(&[1u32, 2]), bytes(&[1u32, 2][..]));
+        assert_eq!(bytes(&[[1u16, 2], [3, 4]]), {
+            let mut expected = LEN_2.to_vec();
+            expected.extend(prefixed(&[1, 0, 2, 0]));
+            expected.extend(prefixed(&[3, 0, 4, 0]));
+            expected
+        });
+    }
+}
@@ -0,0 +1,318 @@
+//! Hashing that detects `Hash` inputs which cannot be deterministic.
+//!
+//! `StrictHasher` recognizes the write patterns of the known non-portable `Hash` impls:
+//! * Slices of integers wider than a byte, like `[u32]` or `Vec<usize>`, are written by `core` as
+//!   their length followed by a single write of their native memory, which bypasses
+//!   `DeterministicHasher`. A `write_usize(n)` followed by a write of `2n`, `4n`, `8n` or `16n`
+//!   bytes is reported, unless it is terminated by `0xFF` like a `str`. Without the `nightly`
+//!   feature, a slice that is followed by a `u8` of `0xFF` is therefore not detected.
+//! * Pointers, like `*const T` or impls based on `Rc::as_ptr`, are written as their address. A
+//!   `usize` above `u32::MAX` is reported, as it is most likely an address on a 64-bit target.
+//!
+//! Values like `TypeId` and `std::thread::ThreadId` are written as plain integers, so they cannot
+//! be told apart from portable input and are not detected.
+//!
+//! ```
+//! use core::hash::Hash;
+//! use deterministic_hash::strict::{NonDeterministicInput, StrictHasher};
+//! use deterministic_hash::DeterministicHasher;
+//! let hasher = DeterministicHasher::new(crc::crc32::Digest::new(crc::crc32::KOOPMAN));
+//! let mut hasher = StrictHasher::new(hasher);
+//! [1u32, 2, 3][..].hash(&mut hasher);
+//! assert_eq!(
+//!     hasher.try_finish(),
+//!     Err(NonDeterministicInput::NativeSlice { len: 3, element_size: 4 })
+//! );
+//! ```
+
+use crate::FixedOutputHasher;
+use core::fmt;
+use core::hash::Hasher;
+
+/// A write pattern of a `Hash` impl that is not portable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NonDeterministicInput {
+    /// A slice of `len` integers of `element_size` bytes was written as its native memory.
+    NativeSlice { len: u64, element_size: usize },
+    /// A `usize` was written that is most likely a pointer address.
+    PointerLike { value: u64 },
+}
+
+impl fmt::Display for NonDeterministicInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NonDeterministicInput::NativeSlice { len, element_size } => write!(
+                f,
+                "slice of {} elements of {} bytes written as native memory",
+                len, element_size
+            ),
+            NonDeterministicInput::PointerLike { value } => {
+                write!(f, "usize {:#x} is likely a pointer address", value)
+            }
+        }
+    }
+}
+
+impl core::error::Error for NonDeterministicInput {}
+
+/// Wrapper around any hasher that detects non-portable `Hash` inputs.
+///
+/// All writes are still forwarded to the inner hasher. The first detected input is reported by
+/// `try_finish`.
+#[derive(Clone, Debug)]
+pub struct StrictHasher<T: Hasher> {
+    inner: T,
+    /// The value of the last write, if it was a `write_usize`.
+    last_usize: Option<u64>,
+    /// A native slice write that is reported unless the next write terminates a `str`.
+    pending: Option<NonDeterministicInput>,
+    error: Option<NonDeterministicInput>,
+}
+
+impl<T: Hasher> StrictHasher<T> {
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            last_usize: None,
+            pending: None,
+            error: None,
+        }
+    }
+
+    /// Returns the hash, or the first non-portable input that was written.
+    pub fn try_finish(&self) -> Result<u64, NonDeterministicInput> {
+        match self.error.or(self.pending) {
+            Some(error) => Err(error),
+            None => Ok(self.inner.finish()),
+        }
+    }
+
+    pub fn as_inner(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn reject(&mut self, error: NonDeterministicInput) {
+        self.error.get_or_insert(error);
+    }
+
+    fn observe_usize(&mut self, i: usize) {
+        self.settle(false);
+        let value = i as u64;
+        if value > u32::MAX as u64 {
+            self.reject(NonDeterministicInput::PointerLike { value });
+        }
+        self.last_usize = Some(value);
+    }
+
+    /// Resolves the state of the previous write, before a write other than `write_usize`.
+    fn settle(&mut self, terminates_str: bool) {
+        self.last_usize = None;
+        if let Some(pending) = self.pending.take() {
+            if !terminates_str {
+                self.reject(pending);
+            }
+        }
+    }
+}
+
+impl<T: Hasher> Hasher for StrictHasher<T> {
+    fn finish(&self) -> u64 {
+        self.inner.finish()
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        let last_usize = self.last_usize;
+        self.settle(false);
+        if let Some(len) = last_usize.filter(|len| *len > 0) {
+            let element_size = bytes.len() as u64 / len;
+            if element_size * len == bytes.len() as u64 && [2, 4, 8, 16].contains(&element_size) {
+                self.pending = Some(NonDeterministicInput::NativeSlice {
+                    len,
+                    element_size: element_size as usize,
+                });
+            }
+        }
+        self.inner.write(bytes);
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.settle(i == 0xFF);
+        self.inner.write_u8(i)
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.settle(false);
+        self.inner.write_u16(i)
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.settle(false);
+        self.inner.write_u32(i)
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.settle(false);
+        self.inner.write_u64(i)
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.settle(false);
+        self.inner.write_u128(i)
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.observe_usize(i);
+        self.inner.write_usize(i)
+    }
+
+    fn write_i8(&mut self, i: i8) {
+        self.settle(false);
+        self.inner.write_i8(i)
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.settle(false);
+        self.inner.write_i16(i)
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.settle(false);
+        self.inner.write_i32(i)
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.settle(false);
+        self.inner.write_i64(i)
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.settle(false);
+        self.inner.write_i128(i)
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.settle(false);
+        self.inner.write_isize(i)
+    }
+
+    #[cfg(feature = "nightly")]
+    fn write_length_prefix(&mut self, len: usize) {
+        self.observe_usize(len);
+        self.inner.write_length_prefix(len)
+    }
+
+    #[cfg(feature = "nightly")]
+    fn write_str(&mut self, s: &str) {
+        self.settle(false);
+        self.inner.write_str(s)
+    }
+}
+
+impl<T: FixedOutputHasher> FixedOutputHasher for StrictHasher<T> {
+    type Output = T::Output;
+
+    fn finish_output(&self) -> T::Output {
+        self.inner.finish_output()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{NonDeterministicInput, StrictHasher};
+    use crate::DeterministicHasher;
+    use core::hash::{Hash, Hasher};
+    use crc::crc32::{Digest, KOOPMAN};
+    use std::string::String;
+    use std::vec;
+
+    fn try_hash<T: Hash + ?Sized>(value: &T) -> Result<u64, NonDeterministicInput> {
+        let mut hasher = StrictHasher::new(DeterministicHasher::new(Digest::new(KOOPMAN)));
+        value.hash(&mut hasher);
+        hasher.try_finish()
+    }
+
+    #[derive(Hash)]
+    struct Portable {
+        id: usize,
+        offset: isize,
+        name: String,
+        bytes: std::vec::Vec<u8>,
+        flag: Option<bool>,
+    }
+
+    #[derive(Hash)]
+    struct Tags {
+        len: usize,
+        name: String,
+    }
+
+    #[test]
+    fn portable() {
+        let value = Portable {
+            id: 0x1337,
+            offset: -1,
+            name: String::from("abcd"),
+            bytes: vec![1, 2, 3],
+            flag: Some(true),
+        };
+        let mut expected = DeterministicHasher::new(Digest::new(KOOPMAN));
+        value.hash(&mut expected);
+        assert_eq!(try_hash(&value), Ok(expected.finish()));
+
+        // A `usize` that happens to match the length of a following `str` is not a slice.
+        assert!(try_hash(&(2usize, "abcd")).is_ok());
+        assert!(try_hash(&vec!["name", "abcdefgh"]).is_ok());
+        assert!(try_hash(&Tags {
+            len: 1,
+            name: String::from("ab"),
+        })
+        .is_ok());
+        assert!(try_hash(&vec![String::from("ab")]).is_ok());
+        assert!(try_hash(&[[1u8, 2]; 3][..]).is_ok());
+    }
+
+    #[test]
+    fn native_slices() {
+        assert_eq!(
+            try_hash(&vec![1u64, 2]),
+            Err(NonDeterministicInput::NativeSlice {
+                len: 2,
+                element_size: 8
+            })
+        );
+        assert_eq!(
+            try_hash(&(&[1i16][..], 3u32)),
+            Err(NonDeterministicInput::NativeSlice {
+                len: 1,
+                element_size: 2
+            })
+        );
+        assert_eq!(
+            try_hash(&[0u16; 0][..]),
+            Ok({
+                let mut expected = DeterministicHasher::new(Digest::new(KOOPMAN));
+                [0u16; 0][..].hash(&mut expected);
+                expected.finish()
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn pointers() {
+        let value = 0u32;
+        let pointer = &value as *const u32;
+        assert_eq!(
+            try_hash(&pointer),
+            Err(NonDeterministicInput::PointerLike {
+                value: pointer as u64
+            })
+        );
+    }
+}
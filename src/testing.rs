@@ -0,0 +1,95 @@
+//! Helpers shared by the tests of the algorithm modules.
+
+extern crate std;
+
+use crate::{BoundaryIndependent, HashAlgorithm};
+use core::hash::Hasher;
+use std::vec::Vec;
+
+/// Collects every byte written to it.
+#[derive(Default)]
+pub(crate) struct Bytes(pub(crate) Vec<u8>);
+
+impl Hasher for Bytes {
+    fn finish(&self) -> u64 {
+        0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+}
+
+/// The multiplier of the upstream xxHash test suite, which also seeds the 64-bit hashers.
+#[cfg(any(feature = "xxhash", feature = "xxh3"))]
+pub(crate) const PRIME64: u64 = 0x9e37_79b1_85eb_ca8d;
+
+/// The sanity buffer of the upstream xxHash test suite.
+#[cfg(any(feature = "xxhash", feature = "xxh3"))]
+pub(crate) fn sanity_buffer<const N: usize>() -> [u8; N] {
+    let mut buffer = [0; N];
+    // The first 32-bit prime of xxHash.
+    let mut generator = 0x9e37_79b1u64;
+    for byte in buffer.iter_mut() {
+        *byte = (generator >> 56) as u8;
+        generator = generator.wrapping_mul(PRIME64);
+    }
+    buffer
+}
+
+/// The xorshift64 generator, to derive inputs and write boundaries from a fixed seed.
+pub(crate) struct Xorshift64(u64);
+
+impl Xorshift64 {
+    pub(crate) const fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a value in `0..=max`.
+    pub(crate) fn up_to(&mut self, max: usize) -> usize {
+        (self.next() % (max as u64 + 1)) as usize
+    }
+}
+
+/// Asserts that writing random inputs in random pieces results in the same output as writing them
+/// at once.
+pub(crate) fn assert_boundary_independent<H: BoundaryIndependent + HashAlgorithm>() {
+    let mut rng = Xorshift64::new(0x2545_f491_4f6c_dd1d);
+    let mut input = [0u8; 600];
+
+    for _ in 0..200 {
+        let len = rng.up_to(input.len());
+        for byte in input[..len].iter_mut() {
+            *byte = rng.next() as u8;
+        }
+        let input = &input[..len];
+
+        let mut whole = H::new_hasher();
+        whole.write(input);
+
+        let mut pieces = H::new_hasher();
+        let mut remaining = input;
+        while !remaining.is_empty() {
+            // Mostly small pieces, including empty ones, with an occasional large piece.
+            let max = core::cmp::min(remaining.len(), 1 << rng.up_to(8));
+            let (piece, rest) = remaining.split_at(rng.up_to(max));
+            pieces.write(piece);
+            remaining = rest;
+        }
+
+        assert_eq!(
+            pieces.finish_output(),
+            whole.finish_output(),
+            "{:02x?}",
+            input
+        );
+        assert_eq!(pieces.finish(), whole.finish());
+    }
+}
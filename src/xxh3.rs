@@ -0,0 +1,731 @@
+//! The XXH3 hash function, in its 64 and 128-bit variants.
+//!
+//! Both hashers are streaming versions of the reference implementation. They keep a buffer of 256
+//! bytes, so prefer to store them on the heap when that matters on a small stack.
+//!
+//! ```
+//! use core::hash::{Hash, Hasher};
+//! use deterministic_hash::xxh3::DeterministicXxh3_64;
+//! let mut hasher = DeterministicXxh3_64::default();
+//! (0x1337 as usize).hash(&mut hasher);
+//! assert_eq!(hasher.finish(), 0x834483ba3271c40f);
+//! ```
+
+use crate::block::{read_u32, read_u64};
+use crate::{
+    BoundaryIndependent, DeterministicBuildHasher, DeterministicHasher, FixedOutputHasher,
+    HashAlgorithm,
+};
+use core::hash::{BuildHasherDefault, Hasher};
+
+const PRIME32_1: u64 = 0x9e37_79b1;
+const PRIME32_2: u64 = 0x85eb_ca77;
+const PRIME32_3: u64 = 0xc2b2_ae3d;
+
+const PRIME64_1: u64 = 0x9e37_79b1_85eb_ca87;
+const PRIME64_2: u64 = 0xc2b2_ae3d_27d4_eb4f;
+const PRIME64_3: u64 = 0x1656_67b1_9e37_79f9;
+const PRIME64_4: u64 = 0x85eb_ca77_c2b2_ae63;
+const PRIME64_5: u64 = 0x27d4_eb2f_1656_67c5;
+
+const PRIME_MX1: u64 = 0x1656_6791_9e37_79f9;
+const PRIME_MX2: u64 = 0x9fb2_1c65_1e98_df25;
+
+const STRIPE_LEN: usize = 64;
+const SECRET_CONSUME_RATE: usize = 8;
+const SECRET_MERGEACCS_START: usize = 11;
+const SECRET_LASTACC_START: usize = 7;
+const SECRET_SIZE_MIN: usize = 136;
+const SECRET_SIZE: usize = 192;
+const STRIPES_PER_BLOCK: usize = (SECRET_SIZE - STRIPE_LEN) / SECRET_CONSUME_RATE;
+const MID_SIZE_MAX: usize = 240;
+const BUFFER_SIZE: usize = 256;
+
+const DEFAULT_SECRET: [u8; SECRET_SIZE] = [
+    0xb8, 0xfe, 0x6c, 0x39, 0x23, 0xa4, 0x4b, 0xbe, 0x7c, 0x01, 0x81, 0x2c, 0xf7, 0x21, 0xad, 0x1c,
+    0xde, 0xd4, 0x6d, 0xe9, 0x83, 0x90, 0x97, 0xdb, 0x72, 0x40, 0xa4, 0xa4, 0xb7, 0xb3, 0x67, 0x1f,
+    0xcb, 0x79, 0xe6, 0x4e, 0xcc, 0xc0, 0xe5, 0x78, 0x82, 0x5a, 0xd0, 0x7d, 0xcc, 0xff, 0x72, 0x21,
+    0xb8, 0x08, 0x46, 0x74, 0xf7, 0x43, 0x24, 0x8e, 0xe0, 0x35, 0x90, 0xe6, 0x81, 0x3a, 0x26, 0x4c,
+    0x3c, 0x28, 0x52, 0xbb, 0x91, 0xc3, 0x00, 0xcb, 0x88, 0xd0, 0x65, 0x8b, 0x1b, 0x53, 0x2e, 0xa3,
+    0x71, 0x64, 0x48, 0x97, 0xa2, 0x0d, 0xf9, 0x4e, 0x38, 0x19, 0xef, 0x46, 0xa9, 0xde, 0xac, 0xd8,
+    0xa8, 0xfa, 0x76, 0x3f, 0xe3, 0x9c, 0x34, 0x3f, 0xf9, 0xdc, 0xbb, 0xc7, 0xc7, 0x0b, 0x4f, 0x1d,
+    0x8a, 0x51, 0xe0, 0x4b, 0xcd, 0xb4, 0x59, 0x31, 0xc8, 0x9f, 0x7e, 0xc9, 0xd9, 0x78, 0x73, 0x64,
+    0xea, 0xc5, 0xac, 0x83, 0x34, 0xd3, 0xeb, 0xc3, 0xc5, 0x81, 0xa0, 0xff, 0xfa, 0x13, 0x63, 0xeb,
+    0x17, 0x0d, 0xdd, 0x51, 0xb7, 0xf0, 0xda, 0x49, 0xd3, 0x16, 0x55, 0x26, 0x29, 0xd4, 0x68, 0x9e,
+    0x2b, 0x16, 0xbe, 0x58, 0x7d, 0x47, 0xa1, 0xfc, 0x8f, 0xf8, 0xb8, 0xd1, 0x7a, 0xd0, 0x31, 0xce,
+    0x45, 0xcb, 0x3a, 0x8f, 0x95, 0x16, 0x04, 0x28, 0xaf, 0xd7, 0xfb, 0xca, 0xbb, 0x4b, 0x40, 0x7e,
+];
+
+const INITIAL_ACC: [u64; 8] = [
+    PRIME32_3, PRIME64_1, PRIME64_2, PRIME64_3, PRIME64_4, PRIME32_2, PRIME64_5, PRIME32_1,
+];
+
+/// The 64-bit XXH3 hasher.
+#[derive(Clone, Debug)]
+pub struct Xxh3_64(State);
+
+/// The 128-bit XXH3 hasher.
+#[derive(Clone, Debug)]
+pub struct Xxh3_128(State);
+
+/// `DeterministicHasher` around the 64-bit XXH3 hasher.
+pub type DeterministicXxh3_64 = DeterministicHasher<Xxh3_64>;
+
+/// `DeterministicBuildHasher` that builds `DeterministicXxh3_64` hashers.
+pub type BuildDeterministicXxh3_64 = DeterministicBuildHasher<BuildHasherDefault<Xxh3_64>>;
+
+/// `DeterministicHasher` around the 128-bit XXH3 hasher.
+pub type DeterministicXxh3_128 = DeterministicHasher<Xxh3_128>;
+
+/// `DeterministicBuildHasher` that builds `DeterministicXxh3_128` hashers.
+pub type BuildDeterministicXxh3_128 = DeterministicBuildHasher<BuildHasherDefault<Xxh3_128>>;
+
+/// The streaming state shared by both variants.
+#[derive(Clone, Debug)]
+struct State {
+    seed: u64,
+    /// The secret derived from the seed, used for inputs longer than `MID_SIZE_MAX`.
+    secret: [u8; SECRET_SIZE],
+    acc: [u64; 8],
+    /// The last bytes written. Bytes beyond `buffered` are the end of the previous stripes.
+    buffer: [u8; BUFFER_SIZE],
+    buffered: usize,
+    /// The number of stripes accumulated in the current block.
+    stripes: usize,
+    len: u64,
+}
+
+fn mul128_fold64(left: u64, right: u64) -> u64 {
+    let product = left as u128 * right as u128;
+    product as u64 ^ (product >> 64) as u64
+}
+
+fn mul64_to128(left: u64, right: u64) -> (u64, u64) {
+    let product = left as u128 * right as u128;
+    (product as u64, (product >> 64) as u64)
+}
+
+fn xorshift64(value: u64, shift: u32) -> u64 {
+    value ^ (value >> shift)
+}
+
+fn xxh64_avalanche(mut h: u64) -> u64 {
+    h ^= h >> 33;
+    h = h.wrapping_mul(PRIME64_2);
+    h ^= h >> 29;
+    h = h.wrapping_mul(PRIME64_3);
+    h ^ (h >> 32)
+}
+
+fn avalanche(h: u64) -> u64 {
+    xorshift64(xorshift64(h, 37).wrapping_mul(PRIME_MX1), 32)
+}
+
+fn rrmxmx(mut h: u64, len: u64) -> u64 {
+    h ^= h.rotate_left(49) ^ h.rotate_left(24);
+    h = h.wrapping_mul(PRIME_MX2);
+    h ^= (h >> 35).wrapping_add(len);
+    h = h.wrapping_mul(PRIME_MX2);
+    xorshift64(h, 28)
+}
+
+fn mix16b(input: &[u8], secret: &[u8], seed: u64) -> u64 {
+    let lo = read_u64(input) ^ read_u64(secret).wrapping_add(seed);
+    let hi = read_u64(&input[8..]) ^ read_u64(&secret[8..]).wrapping_sub(seed);
+    mul128_fold64(lo, hi)
+}
+
+fn mix32b(acc: (u64, u64), first: &[u8], second: &[u8], secret: &[u8], seed: u64) -> (u64, u64) {
+    let lo = acc.0.wrapping_add(mix16b(first, secret, seed))
+        ^ read_u64(second).wrapping_add(read_u64(&second[8..]));
+    let hi = acc.1.wrapping_add(mix16b(second, &secret[16..], seed))
+        ^ read_u64(first).wrapping_add(read_u64(&first[8..]));
+    (lo, hi)
+}
+
+fn derive_secret(seed: u64) -> [u8; SECRET_SIZE] {
+    let mut secret = DEFAULT_SECRET;
+    for pair in secret.chunks_exact_mut(16) {
+        let lo = read_u64(pair).wrapping_add(seed);
+        let hi = read_u64(&pair[8..]).wrapping_sub(seed);
+        pair[..8].copy_from_slice(&lo.to_le_bytes());
+        pair[8..].copy_from_slice(&hi.to_le_bytes());
+    }
+    secret
+}
+
+fn accumulate_512(acc: &mut [u64; 8], stripe: &[u8], secret: &[u8]) {
+    for i in 0..8 {
+        let value = read_u64(&stripe[8 * i..]);
+        let key = value ^ read_u64(&secret[8 * i..]);
+        acc[i ^ 1] = acc[i ^ 1].wrapping_add(value);
+        acc[i] = acc[i].wrapping_add((key & 0xffff_ffff).wrapping_mul(key >> 32));
+    }
+}
+
+fn scramble(acc: &mut [u64; 8], secret: &[u8]) {
+    for (i, lane) in acc.iter_mut().enumerate() {
+        let key = read_u64(&secret[8 * i..]);
+        *lane = (xorshift64(*lane, 47) ^ key).wrapping_mul(PRIME32_1);
+    }
+}
+
+fn merge_accs(acc: &[u64; 8], secret: &[u8], start: u64) -> u64 {
+    let mut result = start;
+    for i in 0..4 {
+        result = result.wrapping_add(mul128_fold64(
+            acc[2 * i] ^ read_u64(&secret[16 * i..]),
+            acc[2 * i + 1] ^ read_u64(&secret[16 * i + 8..]),
+        ));
+    }
+    avalanche(result)
+}
+
+/// Accumulates `count` stripes of `input`, scrambling at the end of every block, and returns the
+/// number of stripes accumulated in the current block.
+fn consume_stripes(
+    acc: &mut [u64; 8],
+    mut stripes: usize,
+    input: &[u8],
+    count: usize,
+    secret: &[u8; SECRET_SIZE],
+) -> usize {
+    for stripe in input.chunks_exact(STRIPE_LEN).take(count) {
+        accumulate_512(acc, stripe, &secret[stripes * SECRET_CONSUME_RATE..]);
+        stripes += 1;
+        if stripes == STRIPES_PER_BLOCK {
+            scramble(acc, &secret[SECRET_SIZE - STRIPE_LEN..]);
+            stripes = 0;
+        }
+    }
+    stripes
+}
+
+fn hash64_0to16(input: &[u8], seed: u64, secret: &[u8]) -> u64 {
+    let len = input.len();
+    if len > 8 {
+        let flip1 = (read_u64(&secret[24..]) ^ read_u64(&secret[32..])).wrapping_add(seed);
+        let flip2 = (read_u64(&secret[40..]) ^ read_u64(&secret[48..])).wrapping_sub(seed);
+        let lo = read_u64(input) ^ flip1;
+        let hi = read_u64(&input[len - 8..]) ^ flip2;
+        avalanche(
+            (len as u64)
+                .wrapping_add(lo.swap_bytes())
+                .wrapping_add(hi)
+                .wrapping_add(mul128_fold64(lo, hi)),
+        )
+    } else if len >= 4 {
+        let seed = seed ^ (((seed as u32).swap_bytes() as u64) << 32);
+        let first = read_u32(input) as u64;
+        let last = read_u32(&input[len - 4..]) as u64;
+        let flip = (read_u64(&secret[8..]) ^ read_u64(&secret[16..])).wrapping_sub(seed);
+        rrmxmx((last.wrapping_add(first << 32)) ^ flip, len as u64)
+    } else if len > 0 {
+        let combined = (input[0] as u32) << 16
+            | (input[len >> 1] as u32) << 24
+            | input[len - 1] as u32
+            | (len as u32) << 8;
+        let flip = ((read_u32(secret) ^ read_u32(&secret[4..])) as u64).wrapping_add(seed);
+        xxh64_avalanche(combined as u64 ^ flip)
+    } else {
+        xxh64_avalanche(seed ^ read_u64(&secret[56..]) ^ read_u64(&secret[64..]))
+    }
+}
+
+fn hash64_17to128(input: &[u8], seed: u64, secret: &[u8]) -> u64 {
+    let len = input.len();
+    let mut acc = (len as u64).wrapping_mul(PRIME64_1);
+    let rounds = (len - 1) / 32;
+    for i in (0..=rounds).rev() {
+        acc = acc.wrapping_add(mix16b(&input[16 * i..], &secret[32 * i..], seed));
+        acc = acc.wrapping_add(mix16b(
+            &input[len - 16 * (i + 1)..],
+            &secret[32 * i + 16..],
+            seed,
+        ));
+    }
+    avalanche(acc)
+}
+
+fn hash64_129to240(input: &[u8], seed: u64, secret: &[u8]) -> u64 {
+    const START_OFFSET: usize = 3;
+    const LAST_OFFSET: usize = 17;
+
+    let len = input.len();
+    let mut acc = (len as u64).wrapping_mul(PRIME64_1);
+    for i in 0..8 {
+        acc = acc.wrapping_add(mix16b(&input[16 * i..], &secret[16 * i..], seed));
+    }
+    acc = avalanche(acc);
+    for i in 8..len / 16 {
+        acc = acc.wrapping_add(mix16b(
+            &input[16 * i..],
+            &secret[16 * (i - 8) + START_OFFSET..],
+            seed,
+        ));
+    }
+    acc = acc.wrapping_add(mix16b(
+        &input[len - 16..],
+        &secret[SECRET_SIZE_MIN - LAST_OFFSET..],
+        seed,
+    ));
+    avalanche(acc)
+}
+
+fn hash128_0to16(input: &[u8], seed: u64, secret: &[u8]) -> u128 {
+    let len = input.len();
+    let (lo, hi) = if len > 8 {
+        let flip_lo = (read_u64(&secret[32..]) ^ read_u64(&secret[40..])).wrapping_sub(seed);
+        let flip_hi = (read_u64(&secret[48..]) ^ read_u64(&secret[56..])).wrapping_add(seed);
+        let input_lo = read_u64(input);
+        let mut input_hi = read_u64(&input[len - 8..]);
+
+        let (mut mul_lo, mut mul_hi) = mul64_to128(input_lo ^ input_hi ^ flip_lo, PRIME64_1);
+        mul_lo = mul_lo.wrapping_add((len as u64 - 1) << 54);
+        input_hi ^= flip_hi;
+        mul_hi = mul_hi
+            .wrapping_add(input_hi)
+            .wrapping_add((input_hi & 0xffff_ffff).wrapping_mul(PRIME32_2 - 1));
+        mul_lo ^= mul_hi.swap_bytes();
+
+        let (lo, hi) = mul64_to128(mul_lo, PRIME64_2);
+        (
+            avalanche(lo),
+            avalanche(hi.wrapping_add(mul_hi.wrapping_mul(PRIME64_2))),
+        )
+    } else if len >= 4 {
+        let seed = seed ^ (((seed as u32).swap_bytes() as u64) << 32);
+        let first = read_u32(input) as u64;
+        let last = read_u32(&input[len - 4..]) as u64;
+        let flip = (read_u64(&secret[16..]) ^ read_u64(&secret[24..])).wrapping_add(seed);
+        let keyed = first.wrapping_add(last << 32) ^ flip;
+
+        let (mut lo, mut hi) = mul64_to128(keyed, PRIME64_1.wrapping_add((len as u64) << 2));
+        hi = hi.wrapping_add(lo << 1);
+        lo ^= hi >> 3;
+        lo = xorshift64(lo, 35).wrapping_mul(PRIME_MX2);
+        (xorshift64(lo, 28), avalanche(hi))
+    } else if len > 0 {
+        let combined_lo = (input[0] as u32) << 16
+            | (input[len >> 1] as u32) << 24
+            | input[len - 1] as u32
+            | (len as u32) << 8;
+        let combined_hi = combined_lo.swap_bytes().rotate_left(13);
+        let flip_lo = ((read_u32(secret) ^ read_u32(&secret[4..])) as u64).wrapping_add(seed);
+        let flip_hi =
+            ((read_u32(&secret[8..]) ^ read_u32(&secret[12..])) as u64).wrapping_sub(seed);
+        (
+            xxh64_avalanche(combined_lo as u64 ^ flip_lo),
+            xxh64_avalanche(combined_hi as u64 ^ flip_hi),
+        )
+    } else {
+        (
+            xxh64_avalanche(seed ^ read_u64(&secret[64..]) ^ read_u64(&secret[72..])),
+            xxh64_avalanche(seed ^ read_u64(&secret[80..]) ^ read_u64(&secret[88..])),
+        )
+    };
+    (hi as u128) << 64 | lo as u128
+}
+
+fn finish128_mid((lo, hi): (u64, u64), len: u64, seed: u64) -> u128 {
+    let result_lo = lo.wrapping_add(hi);
+    let result_hi = lo
+        .wrapping_mul(PRIME64_1)
+        .wrapping_add(hi.wrapping_mul(PRIME64_4))
+        .wrapping_add(len.wrapping_sub(seed).wrapping_mul(PRIME64_2));
+    (0u64.wrapping_sub(avalanche(result_hi)) as u128) << 64 | avalanche(result_lo) as u128
+}
+
+fn hash128_17to128(input: &[u8], seed: u64, secret: &[u8]) -> u128 {
+    let len = input.len();
+    let mut acc = ((len as u64).wrapping_mul(PRIME64_1), 0);
+    let rounds = (len - 1) / 32;
+    for i in (0..=rounds).rev() {
+        acc = mix32b(
+            acc,
+            &input[16 * i..],
+            &input[len - 16 * (i + 1)..],
+            &secret[32 * i..],
+            seed,
+        );
+    }
+    finish128_mid(acc, len as u64, seed)
+}
+
+fn hash128_129to240(input: &[u8], seed: u64, secret: &[u8]) -> u128 {
+    const START_OFFSET: usize = 3;
+    const LAST_OFFSET: usize = 17;
+
+    let len = input.len();
+    let mut acc = ((len as u64).wrapping_mul(PRIME64_1), 0);
+    for i in 0..4 {
+        acc = mix32b(
+            acc,
+            &input[32 * i..],
+            &input[32 * i + 16..],
+            &secret[32 * i..],
+            seed,
+        );
+    }
+    acc = (avalanche(acc.0), avalanche(acc.1));
+    for i in 4..len / 32 {
+        acc = mix32b(
+            acc,
+            &input[32 * i..],
+            &input[32 * i + 16..],
+            &secret[START_OFFSET + 32 * (i - 4)..],
+            seed,
+        );
+    }
+    acc = mix32b(
+        acc,
+        &input[len - 16..],
+        &input[len - 32..],
+        &secret[SECRET_SIZE_MIN - LAST_OFFSET - 16..],
+        0u64.wrapping_sub(seed),
+    );
+    finish128_mid(acc, len as u64, seed)
+}
+
+impl State {
+    fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            secret: if seed == 0 {
+                DEFAULT_SECRET
+            } else {
+                derive_secret(seed)
+            },
+            acc: INITIAL_ACC,
+            buffer: [0; BUFFER_SIZE],
+            buffered: 0,
+            stripes: 0,
+            len: 0,
+        }
+    }
+
+    fn write(&mut self, mut bytes: &[u8]) {
+        self.len += bytes.len() as u64;
+
+        if self.buffered + bytes.len() <= BUFFER_SIZE {
+            self.buffer[self.buffered..self.buffered + bytes.len()].copy_from_slice(bytes);
+            self.buffered += bytes.len();
+            return;
+        }
+
+        // Only consume the buffer when more bytes follow, such that the last stripe is always
+        // available when finishing.
+        if self.buffered > 0 {
+            let fill = BUFFER_SIZE - self.buffered;
+            self.buffer[self.buffered..].copy_from_slice(&bytes[..fill]);
+            bytes = &bytes[fill..];
+            self.stripes = consume_stripes(
+                &mut self.acc,
+                self.stripes,
+                &self.buffer,
+                BUFFER_SIZE / STRIPE_LEN,
+                &self.secret,
+            );
+            self.buffered = 0;
+        }
+
+        if bytes.len() > BUFFER_SIZE {
+            let consumed = (bytes.len() - 1) / BUFFER_SIZE * BUFFER_SIZE;
+            self.stripes = consume_stripes(
+                &mut self.acc,
+                self.stripes,
+                &bytes[..consumed],
+                consumed / STRIPE_LEN,
+                &self.secret,
+            );
+            self.buffer[BUFFER_SIZE - STRIPE_LEN..]
+                .copy_from_slice(&bytes[consumed - STRIPE_LEN..consumed]);
+            bytes = &bytes[consumed..];
+        }
+
+        self.buffer[..bytes.len()].copy_from_slice(bytes);
+        self.buffered = bytes.len();
+    }
+
+    /// Returns the accumulators after the buffered bytes, for inputs longer than `MID_SIZE_MAX`.
+    fn finish_acc(&self) -> [u64; 8] {
+        let mut acc = self.acc;
+        let last_stripe_secret = &self.secret[SECRET_SIZE - STRIPE_LEN - SECRET_LASTACC_START..];
+        if self.buffered >= STRIPE_LEN {
+            let count = (self.buffered - 1) / STRIPE_LEN;
+            consume_stripes(&mut acc, self.stripes, &self.buffer, count, &self.secret);
+            accumulate_512(
+                &mut acc,
+                &self.buffer[self.buffered - STRIPE_LEN..self.buffered],
+                last_stripe_secret,
+            );
+        } else {
+            let catch_up = STRIPE_LEN - self.buffered;
+            let mut last_stripe = [0u8; STRIPE_LEN];
+            last_stripe[..catch_up].copy_from_slice(&self.buffer[BUFFER_SIZE - catch_up..]);
+            last_stripe[catch_up..].copy_from_slice(&self.buffer[..self.buffered]);
+            accumulate_512(&mut acc, &last_stripe, last_stripe_secret);
+        }
+        acc
+    }
+
+    fn finish64(&self) -> u64 {
+        if self.len > MID_SIZE_MAX as u64 {
+            let acc = self.finish_acc();
+            merge_accs(
+                &acc,
+                &self.secret[SECRET_MERGEACCS_START..],
+                self.len.wrapping_mul(PRIME64_1),
+            )
+        } else {
+            let input = &self.buffer[..self.buffered];
+            if input.len() <= 16 {
+                hash64_0to16(input, self.seed, &DEFAULT_SECRET)
+            } else if input.len() <= 128 {
+                hash64_17to128(input, self.seed, &DEFAULT_SECRET)
+            } else {
+                hash64_129to240(input, self.seed, &DEFAULT_SECRET)
+            }
+        }
+    }
+
+    fn finish128(&self) -> u128 {
+        if self.len > MID_SIZE_MAX as u64 {
+            let acc = self.finish_acc();
+            let lo = merge_accs(
+                &acc,
+                &self.secret[SECRET_MERGEACCS_START..],
+                self.len.wrapping_mul(PRIME64_1),
+            );
+            let hi = merge_accs(
+                &acc,
+                &self.secret[SECRET_SIZE - 64 - SECRET_MERGEACCS_START..],
+                !self.len.wrapping_mul(PRIME64_2),
+            );
+            (hi as u128) << 64 | lo as u128
+        } else {
+            let input = &self.buffer[..self.buffered];
+            if input.len() <= 16 {
+                hash128_0to16(input, self.seed, &DEFAULT_SECRET)
+            } else if input.len() <= 128 {
+                hash128_17to128(input, self.seed, &DEFAULT_SECRET)
+            } else {
+                hash128_129to240(input, self.seed, &DEFAULT_SECRET)
+            }
+        }
+    }
+}
+
+impl Xxh3_64 {
+    pub fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self(State::new(seed))
+    }
+}
+
+impl Xxh3_128 {
+    pub fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self(State::new(seed))
+    }
+
+    /// Returns the 128-bit hash of the bytes written so far.
+    pub fn finish128(&self) -> u128 {
+        self.0.finish128()
+    }
+}
+
+impl Default for Xxh3_64 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Default for Xxh3_128 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for Xxh3_64 {
+    fn finish(&self) -> u64 {
+        self.0.finish64()
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes);
+    }
+}
+
+/// Returns the low 64 bits of the 128-bit hash.
+impl Hasher for Xxh3_128 {
+    fn finish(&self) -> u64 {
+        self.finish128() as u64
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes);
+    }
+}
+
+impl FixedOutputHasher for Xxh3_64 {
+    type Output = u64;
+
+    fn finish_output(&self) -> u64 {
+        self.finish()
+    }
+}
+
+impl FixedOutputHasher for Xxh3_128 {
+    type Output = u128;
+
+    fn finish_output(&self) -> u128 {
+        self.finish128()
+    }
+}
+
+impl HashAlgorithm for Xxh3_64 {
+    fn new_hasher() -> Self {
+        Self::new()
+    }
+}
+
+impl BoundaryIndependent for Xxh3_64 {}
+
+impl HashAlgorithm for Xxh3_128 {
+    fn new_hasher() -> Self {
+        Self::new()
+    }
+}
+
+impl BoundaryIndependent for Xxh3_128 {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Xxh3_128, Xxh3_64};
+    use crate::testing::{assert_boundary_independent, sanity_buffer, PRIME64};
+    use core::hash::Hasher;
+
+    const VECTORS_64: [(usize, u64, u64); 13] = [
+        (0, 0x2d06800538d394c2, 0xa8a6b918b2f0364a),
+        (1, 0xc44bdff4074eecdb, 0x032be332dd766ef8),
+        (6, 0x27b56a84cd2d7325, 0x84589c116ab59ab9),
+        (12, 0xa713daf0dfbb77e7, 0xe7303e1b2336de0e),
+        (24, 0xa3fe70bf9d3510eb, 0x850e80fc35bdd690),
+        (48, 0x397da259ecba1f11, 0xadc2cbaa44acc616),
+        (80, 0xbcdefbbb2c47c90a, 0xc6dd0cb699532e73),
+        (195, 0xcd94217ee362ec3a, 0xba68003d370cb3d9),
+        (403, 0xcdeb804d65c6dea4, 0x6259f6ecfd6443fd),
+        (512, 0x617e49599013cb6b, 0x3ce457de14c27708),
+        (2048, 0xdd59e2c3a5f038e0, 0x66f81670669ababc),
+        (2240, 0x6e73a90539cf2948, 0x757ba8487d1b5247),
+        (2367, 0xcb37aeb9e5d361ed, 0xd2db3415b942b42a),
+    ];
+
+    const VECTORS_128: [(usize, u128, u128); 13] = [
+        (
+            0,
+            0x99aa06d3014798d86001c324468d497f,
+            0x00feaa732a3ce25ea986dfc5d7605bfe,
+        ),
+        (
+            1,
+            0xa6cd5e9392000f6ac44bdff4074eecdb,
+            0x20e49abcc53b3842032be332dd766ef8,
+        ),
+        (
+            6,
+            0x082afe0b8162d12a3e7039bdda43cfc6,
+            0x014bd95a51ca5ddbc5b54d56038e4e40,
+        ),
+        (
+            12,
+            0x6e3efd8fc7802b18061a192713f69ad9,
+            0xff0d60acd02ed4015d92b5d7190b12d1,
+        ),
+        (
+            24,
+            0x0ce966e4678d37611e7044d28b1b901d,
+            0xd7895ded1f62559dc6cbf92a70680b19,
+        ),
+        (
+            48,
+            0xa002ac4e5478227ef942219aed80f67b,
+            0xbc689f4c0152fb443a94d91333ed395a,
+        ),
+        (
+            80,
+            0xfdf2cefde9eaac8a454ae6bf7a8a532d,
+            0x19bf02d69bc56833a5eac764d1ff1166,
+        ),
+        (
+            195,
+            0x7729543a26b207ee3fb593c086a66075,
+            0x0326104c4d4849e7cf9d9ec2c8c9913f,
+        ),
+        (
+            403,
+            0x1b6de21e332dd73dcdeb804d65c6dea4,
+            0xbed311971e0be8f26259f6ecfd6443fd,
+        ),
+        (
+            512,
+            0x18d2d110dcc9bca1617e49599013cb6b,
+            0x925d06b8ec5b80403ce457de14c27708,
+        ),
+        (
+            2048,
+            0xf736557fd47073a5dd59e2c3a5f038e0,
+            0x23cc3a2e75ebaaea66f81670669ababc,
+        ),
+        (
+            2240,
+            0xccb134fbfa7ce49d6e73a90539cf2948,
+            0xe40842f585875ba9757ba8487d1b5247,
+        ),
+        (
+            2367,
+            0xe89c0f6ff369b427cb37aeb9e5d361ed,
+            0xccb7a94cca1a6496d2db3415b942b42a,
+        ),
+    ];
+
+    #[test]
+    fn reference_vectors() {
+        let buffer = sanity_buffer::<2367>();
+        for ((len, xxh3_64, xxh3_64_seeded), (_, xxh3_128, xxh3_128_seeded)) in
+            VECTORS_64.iter().zip(VECTORS_128.iter())
+        {
+            for chunk_size in [1, 7, 64, 255, 256, 257, 1000, 4096].iter() {
+                let mut hashers = (
+                    Xxh3_64::new(),
+                    Xxh3_64::with_seed(PRIME64),
+                    Xxh3_128::new(),
+                    Xxh3_128::with_seed(PRIME64),
+                );
+                for chunk in buffer[..*len].chunks(*chunk_size) {
+                    hashers.0.write(chunk);
+                    hashers.1.write(chunk);
+                    hashers.2.write(chunk);
+                    hashers.3.write(chunk);
+                }
+                assert_eq!(hashers.0.finish(), *xxh3_64, "length {}", len);
+                assert_eq!(hashers.1.finish(), *xxh3_64_seeded, "length {}", len);
+                assert_eq!(hashers.2.finish128(), *xxh3_128, "length {}", len);
+                assert_eq!(hashers.3.finish128(), *xxh3_128_seeded, "length {}", len);
+            }
+        }
+    }
+
+    #[test]
+    fn boundary_independent() {
+        assert_boundary_independent::<Xxh3_64>();
+        assert_boundary_independent::<Xxh3_128>();
+    }
+}
@@ -0,0 +1,328 @@
+//! The xxHash32 and xxHash64 hash functions.
+//!
+//! ```
+//! use core::hash::{Hash, Hasher};
+//! use deterministic_hash::xxhash::DeterministicXxh64;
+//! let mut hasher = DeterministicXxh64::default();
+//! (0x1337 as usize).hash(&mut hasher);
+//! assert_eq!(hasher.finish(), 0x421e8a3f872915b7);
+//! ```
+
+use crate::block::{read_u32, read_u64, BlockBuffer};
+use crate::{
+    BoundaryIndependent, DeterministicBuildHasher, DeterministicHasher, FixedOutputHasher,
+    HashAlgorithm,
+};
+use core::hash::{BuildHasherDefault, Hasher};
+
+const PRIME32_1: u32 = 0x9e37_79b1;
+const PRIME32_2: u32 = 0x85eb_ca77;
+const PRIME32_3: u32 = 0xc2b2_ae3d;
+const PRIME32_4: u32 = 0x27d4_eb2f;
+const PRIME32_5: u32 = 0x1656_67b1;
+
+const PRIME64_1: u64 = 0x9e37_79b1_85eb_ca87;
+const PRIME64_2: u64 = 0xc2b2_ae3d_27d4_eb4f;
+const PRIME64_3: u64 = 0x1656_67b1_9e37_79f9;
+const PRIME64_4: u64 = 0x85eb_ca77_c2b2_ae63;
+const PRIME64_5: u64 = 0x27d4_eb2f_1656_67c5;
+
+/// The xxHash32 hasher.
+#[derive(Clone, Debug)]
+pub struct Xxh32 {
+    seed: u32,
+    acc: [u32; 4],
+    stripes: BlockBuffer<16>,
+}
+
+/// The xxHash64 hasher.
+#[derive(Clone, Debug)]
+pub struct Xxh64 {
+    seed: u64,
+    acc: [u64; 4],
+    stripes: BlockBuffer<32>,
+}
+
+/// `DeterministicHasher` around the xxHash32 hasher.
+pub type DeterministicXxh32 = DeterministicHasher<Xxh32>;
+
+/// `DeterministicBuildHasher` that builds `DeterministicXxh32` hashers.
+pub type BuildDeterministicXxh32 = DeterministicBuildHasher<BuildHasherDefault<Xxh32>>;
+
+/// `DeterministicHasher` around the xxHash64 hasher.
+pub type DeterministicXxh64 = DeterministicHasher<Xxh64>;
+
+/// `DeterministicBuildHasher` that builds `DeterministicXxh64` hashers.
+pub type BuildDeterministicXxh64 = DeterministicBuildHasher<BuildHasherDefault<Xxh64>>;
+
+impl Xxh32 {
+    pub const fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    pub const fn with_seed(seed: u32) -> Self {
+        Self {
+            seed,
+            acc: [
+                seed.wrapping_add(PRIME32_1).wrapping_add(PRIME32_2),
+                seed.wrapping_add(PRIME32_2),
+                seed,
+                seed.wrapping_sub(PRIME32_1),
+            ],
+            stripes: BlockBuffer::new(),
+        }
+    }
+
+    fn round(acc: u32, input: u32) -> u32 {
+        acc.wrapping_add(input.wrapping_mul(PRIME32_2))
+            .rotate_left(13)
+            .wrapping_mul(PRIME32_1)
+    }
+
+    /// Returns the 32-bit hash of the bytes written so far.
+    pub fn finish32(&self) -> u32 {
+        let mut h = if self.stripes.len() >= 16 {
+            let [v1, v2, v3, v4] = self.acc;
+            v1.rotate_left(1)
+                .wrapping_add(v2.rotate_left(7))
+                .wrapping_add(v3.rotate_left(12))
+                .wrapping_add(v4.rotate_left(18))
+        } else {
+            self.seed.wrapping_add(PRIME32_5)
+        };
+        h = h.wrapping_add(self.stripes.len() as u32);
+
+        let mut words = self.stripes.tail().chunks_exact(4);
+        for word in &mut words {
+            h = h.wrapping_add(read_u32(word).wrapping_mul(PRIME32_3));
+            h = h.rotate_left(17).wrapping_mul(PRIME32_4);
+        }
+        for byte in words.remainder() {
+            h = h.wrapping_add((*byte as u32).wrapping_mul(PRIME32_5));
+            h = h.rotate_left(11).wrapping_mul(PRIME32_1);
+        }
+
+        h ^= h >> 15;
+        h = h.wrapping_mul(PRIME32_2);
+        h ^= h >> 13;
+        h = h.wrapping_mul(PRIME32_3);
+        h ^ (h >> 16)
+    }
+}
+
+impl Xxh64 {
+    pub const fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    pub const fn with_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            acc: [
+                seed.wrapping_add(PRIME64_1).wrapping_add(PRIME64_2),
+                seed.wrapping_add(PRIME64_2),
+                seed,
+                seed.wrapping_sub(PRIME64_1),
+            ],
+            stripes: BlockBuffer::new(),
+        }
+    }
+
+    fn round(acc: u64, input: u64) -> u64 {
+        acc.wrapping_add(input.wrapping_mul(PRIME64_2))
+            .rotate_left(31)
+            .wrapping_mul(PRIME64_1)
+    }
+
+    fn merge_round(acc: u64, value: u64) -> u64 {
+        (acc ^ Self::round(0, value))
+            .wrapping_mul(PRIME64_1)
+            .wrapping_add(PRIME64_4)
+    }
+}
+
+impl Default for Xxh32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Default for Xxh64 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the 32-bit hash zero-extended to a `u64`.
+impl Hasher for Xxh32 {
+    fn finish(&self) -> u64 {
+        self.finish32() as u64
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        let acc = &mut self.acc;
+        self.stripes.write(bytes, |stripe| {
+            for (lane, word) in acc.iter_mut().zip(stripe.chunks_exact(4)) {
+                *lane = Self::round(*lane, read_u32(word));
+            }
+        });
+    }
+}
+
+impl Hasher for Xxh64 {
+    fn finish(&self) -> u64 {
+        let mut h = if self.stripes.len() >= 32 {
+            let [v1, v2, v3, v4] = self.acc;
+            let mut h = v1
+                .rotate_left(1)
+                .wrapping_add(v2.rotate_left(7))
+                .wrapping_add(v3.rotate_left(12))
+                .wrapping_add(v4.rotate_left(18));
+            for v in self.acc.iter() {
+                h = Self::merge_round(h, *v);
+            }
+            h
+        } else {
+            self.seed.wrapping_add(PRIME64_5)
+        };
+        h = h.wrapping_add(self.stripes.len());
+
+        let mut remaining = self.stripes.tail();
+        while remaining.len() >= 8 {
+            h ^= Self::round(0, read_u64(remaining));
+            h = h
+                .rotate_left(27)
+                .wrapping_mul(PRIME64_1)
+                .wrapping_add(PRIME64_4);
+            remaining = &remaining[8..];
+        }
+        if remaining.len() >= 4 {
+            h ^= (read_u32(remaining) as u64).wrapping_mul(PRIME64_1);
+            h = h
+                .rotate_left(23)
+                .wrapping_mul(PRIME64_2)
+                .wrapping_add(PRIME64_3);
+            remaining = &remaining[4..];
+        }
+        for byte in remaining {
+            h ^= (*byte as u64).wrapping_mul(PRIME64_5);
+            h = h.rotate_left(11).wrapping_mul(PRIME64_1);
+        }
+
+        h ^= h >> 33;
+        h = h.wrapping_mul(PRIME64_2);
+        h ^= h >> 29;
+        h = h.wrapping_mul(PRIME64_3);
+        h ^ (h >> 32)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        let acc = &mut self.acc;
+        self.stripes.write(bytes, |stripe| {
+            for (lane, word) in acc.iter_mut().zip(stripe.chunks_exact(8)) {
+                *lane = Self::round(*lane, read_u64(word));
+            }
+        });
+    }
+}
+
+impl FixedOutputHasher for Xxh32 {
+    type Output = u32;
+
+    fn finish_output(&self) -> u32 {
+        self.finish32()
+    }
+}
+
+impl FixedOutputHasher for Xxh64 {
+    type Output = u64;
+
+    fn finish_output(&self) -> u64 {
+        self.finish()
+    }
+}
+
+impl HashAlgorithm for Xxh32 {
+    fn new_hasher() -> Self {
+        Self::new()
+    }
+}
+
+impl BoundaryIndependent for Xxh32 {}
+
+impl HashAlgorithm for Xxh64 {
+    fn new_hasher() -> Self {
+        Self::new()
+    }
+}
+
+impl BoundaryIndependent for Xxh64 {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Xxh32, Xxh64, PRIME32_1};
+    use crate::testing::{assert_boundary_independent, sanity_buffer, PRIME64};
+    use core::hash::Hasher;
+
+    #[test]
+    fn reference_vectors() {
+        const VECTORS: [(usize, u32, u32, u64, u64); 4] = [
+            (
+                0,
+                0x02cc5d05,
+                0x36b78ae7,
+                0xef46db3751d8e999,
+                0x0b303d920ec349df,
+            ),
+            (
+                1,
+                0xcf65b03e,
+                0xb4545aa4,
+                0xe934a84adb052768,
+                0x9c6678669fcd2e6d,
+            ),
+            (
+                14,
+                0x1208e7e2,
+                0x6af1d1fe,
+                0x8282dcc4994e35c8,
+                0x12dcd5db160cc92b,
+            ),
+            (
+                222,
+                0x5bd11dbd,
+                0x58803c5f,
+                0xb641ae8cb691c174,
+                0xccb064ac93ebb562,
+            ),
+        ];
+
+        let buffer = sanity_buffer::<222>();
+        for (len, xxh32, xxh32_seeded, xxh64, xxh64_seeded) in VECTORS.iter() {
+            for chunk_size in 1..=33 {
+                let mut hashers = (
+                    Xxh32::new(),
+                    Xxh32::with_seed(PRIME32_1),
+                    Xxh64::new(),
+                    Xxh64::with_seed(PRIME64),
+                );
+                for chunk in buffer[..*len].chunks(chunk_size) {
+                    hashers.0.write(chunk);
+                    hashers.1.write(chunk);
+                    hashers.2.write(chunk);
+                    hashers.3.write(chunk);
+                }
+                assert_eq!(hashers.0.finish32(), *xxh32);
+                assert_eq!(hashers.1.finish32(), *xxh32_seeded);
+                assert_eq!(hashers.2.finish(), *xxh64);
+                assert_eq!(hashers.3.finish(), *xxh64_seeded);
+            }
+        }
+    }
+
+    #[test]
+    fn boundary_independent() {
+        assert_boundary_independent::<Xxh32>();
+        assert_boundary_independent::<Xxh64>();
+    }
+}